@@ -0,0 +1,4283 @@
+#![allow(non_snake_case)]
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Write};
+use serde_json::Result;
+use std::env;
+use std::fmt;
+use std::fs::File;
+use std::process;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use term_size::dimensions_stdout;
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+use clap::{Parser, ValueEnum};
+use regex::Regex;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use owo_colors::OwoColorize;
+use rayon::prelude::*;
+use dialoguer::FuzzySelect;
+use dialoguer::theme::ColorfulTheme;
+use schemars::{schema_for, JsonSchema};
+use std::cell::RefCell;
+use std::sync::atomic::{self, AtomicU8};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// How much besides errors goes to stderr; set once from the command line in run
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Verbosity {
+    Silent,
+    Normal,
+    Verbose,
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+fn verbosity_at_least(level: Verbosity) -> bool {
+    VERBOSITY.load(atomic::Ordering::Relaxed) >= level as u8
+}
+
+// Diagnostics always go to stderr, so stdout stays purely the data
+macro_rules! log_warning {
+    ($($arg:tt)*) => {
+        if verbosity_at_least(Verbosity::Normal) {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+macro_rules! log_verbose {
+    ($($arg:tt)*) => {
+        if verbosity_at_least(Verbosity::Verbose) {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct Reward {
+    pub rarity: String,
+    pub chance: f64,
+    pub item: RewardItem,
+}
+
+// How reward chances are printed, like "11.11%" or, as odds, "1/9"
+#[derive(Debug, Clone, Copy)]
+struct ChanceFormat {
+    precision: usize,
+    as_fraction: bool,
+}
+
+impl Default for ChanceFormat {
+    fn default() -> ChanceFormat {
+        ChanceFormat { precision: 2, as_fraction: false }
+    }
+}
+
+impl ChanceFormat {
+    // Odds are rounded to a whole "1 in N", so 11.11% and 11.0% both read as "1/9"
+    fn format(&self, chance: f64) -> String {
+        if !self.as_fraction {
+            format!("{:.*}%", self.precision, chance)
+        } else if chance > 0.0 {
+            format!("1/{}", (100.0 / chance).round())
+        } else {
+            String::from("0")
+        }
+    }
+
+    fn describe(&self, reward: &Reward) -> String {
+        self.describe_with(reward, |rarity| rarity.to_string())
+    }
+
+    // Like describe, with the rarity passed through a function first, e.g. to color it in the default format
+    fn describe_with(&self, reward: &Reward, rarity: impl Fn(&str) -> String) -> String {
+        format!("[{} {}] {}", rarity(&reward.rarity), self.format(reward.chance), reward.item.name)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct RewardItem {
+    pub name: String,
+    pub uniqueName: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warframeMarket: Option<WarframeMarket>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct WarframeMarket {
+    pub id: String,
+    pub urlName: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct Patchlog {
+    pub name: String,
+    pub date: String,
+    pub url: String,
+    pub additions: String,
+    pub changes: String,
+    pub fixes: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct Component {
+    pub name: String,
+    pub uniqueName: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+    pub tradable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub productCategory: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct Introduced {
+    pub name: String,
+    pub url: String,
+    pub aliases: Vec<String>,
+    pub parent: String,
+    pub date: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct Item {
+    pub name: String,
+    pub uniqueName: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub tradable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub productCategory: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patchlogs: Option<Vec<Patchlog>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub components: Option<Vec<Component>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub introduced: Option<Introduced>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimatedVaultDate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rewards: Option<Vec<Reward>>, // Include the rewards property
+    // The item export calls this "marketInfo", the relic reward entries call it "warframeMarket"
+    #[serde(default, alias = "marketInfo", skip_serializing_if = "Option::is_none")]
+    pub warframeMarket: Option<WarframeMarket>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub masterable: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub masteryReq: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imageName: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vaulted: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RelicType {
+    Lith,
+    Meso,
+    Neo,
+    Axi,
+}
+
+impl RelicType {
+    fn from_str(s: &str) -> Option<RelicType> {
+        match s.to_lowercase().as_str() {
+            "lith" => Some(RelicType::Lith),
+            "meso" => Some(RelicType::Meso),
+            "neo" => Some(RelicType::Neo),
+            "axi" => Some(RelicType::Axi),
+            _ => None,
+        }
+    }
+
+    // Relic uniqueNames encode the era as a void tier, e.g. "/Lotus/Types/Game/Projections/T3VoidProjectionVoltPrimeBronze".
+    // Tiers 1-4 are the four eras; T5 is Requiem, which has no era.
+    pub fn from_unique_name(unique_name: &str) -> Option<RelicType> {
+        let tier_start = unique_name.find("VoidProjection")?.checked_sub(2)?;
+        match unique_name.get(tier_start..tier_start + 2)? {
+            "T1" => Some(RelicType::Lith),
+            "T2" => Some(RelicType::Meso),
+            "T3" => Some(RelicType::Neo),
+            "T4" => Some(RelicType::Axi),
+            _ => None,
+        }
+    }
+
+    fn parse(s: &str) -> std::result::Result<RelicType, String> {
+        RelicType::from_str(s).ok_or_else(|| String::from("unknown relic type (valid types: lith, meso, neo, axi)"))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RelicType::Lith => "Lith",
+            RelicType::Meso => "Meso",
+            RelicType::Neo => "Neo",
+            RelicType::Axi => "Axi",
+        }
+    }
+} 
+
+// How far a relic has been upgraded with Void Traces, raising the chances of its rarer rewards
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Refinement {
+    Intact,
+    Exceptional,
+    Flawless,
+    Radiant,
+}
+
+impl Refinement {
+    const ALL: [Refinement; 4] = [Refinement::Intact, Refinement::Exceptional, Refinement::Flawless, Refinement::Radiant];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Boxed, human-readable listing of every field
+    #[default]
+    Default,
+    /// One name per line for pickers like fzf (also accepted as "search"); with --relic, each relic's short name ("Axi A1") once
+    #[value(name = "names", alias = "search")]
+    Search,
+    /// One compact JSON object per line
+    Ndjson,
+    /// CSV with a header row; see --columns
+    Csv,
+    /// A single JSON array of the matching items; see --pretty
+    Json,
+    /// Tab-separated name, type and tradable, one item per line, for grep and awk
+    Line,
+    /// GitHub-flavored Markdown table; see --columns
+    #[value(name = "md", alias = "markdown")]
+    Markdown,
+    /// The image URL of each item that has one; see --image-base-url
+    Image,
+    /// The warframe.market page of each item that has one; see --market-base
+    Market,
+    /// A YAML list of the matching items
+    Yaml,
+    /// A TOML array of tables named "items", since TOML has no top-level arrays
+    Toml,
+}
+
+impl Item {
+    // Everything before the refinement or "Relic", so multi-word codes like "Requiem I" aren't merged with each other.
+    // Never shorter than two words, so a bare "Axi Relic" keeps its name.
+    pub fn get_relic_short_name(&self) -> String {
+        let words: Vec<&str> = self.name.split_whitespace().collect();
+        let code_end = words.iter().position(|word| is_relic_descriptor(word)).unwrap_or(words.len());
+        words[..code_end.max(2).min(words.len())].join(" ")
+    }
+
+    // List the most likely drops first
+    pub fn rewards_by_chance(&self) -> Vec<&Reward> {
+        let mut rewards: Vec<&Reward> = self.rewards.iter().flatten().collect();
+        rewards.sort_by(|a, b| b.chance.total_cmp(&a.chance));
+        rewards
+    }
+
+    // Relic names start with their era, e.g. "Lith A1 Intact"; otherwise fall back to the void tier in the uniqueName
+    pub fn relic_type(&self) -> Option<RelicType> {
+        self.name
+            .split_whitespace()
+            .next()
+            .and_then(RelicType::from_str)
+            .or_else(|| RelicType::from_unique_name(&self.uniqueName))
+    }
+
+    // Only known when the export spells it out at the end of the name, e.g. "Lith A1 Radiant"
+    pub fn refinement(&self) -> Option<Refinement> {
+        let last_word = self.name.split_whitespace().last()?;
+        Refinement::ALL.into_iter().find(|refinement| value_name(*refinement).eq_ignore_ascii_case(last_word))
+    }
+
+    pub fn introduced_date(&self) -> Option<NaiveDate> {
+        parse_date(&self.introduced.as_ref()?.date)
+    }
+
+    pub fn estimated_vault_date(&self) -> Option<NaiveDate> {
+        parse_date(self.estimatedVaultDate.as_ref()?)
+    }
+
+    // The export's "vaulted" flag when it has one, otherwise whether the estimated vault date has passed.
+    // Items with neither count as available.
+    pub fn is_vaulted(&self, today: NaiveDate) -> bool {
+        match self.vaulted {
+            Some(vaulted) => vaulted,
+            None => self.estimated_vault_date().is_some_and(|vault_date| vault_date < today),
+        }
+    }
+
+    // Relics are told apart by their type, "Relic" unless --relic-type-label says otherwise
+    pub fn is_relic(&self, relic_type_label: &str) -> bool {
+        self.type_ == relic_type_label
+    }
+
+    // Rank of the rarest known reward rarity, higher being rarer; None without rewards
+    fn best_reward_rarity(&self) -> Option<usize> {
+        self.rewards.iter().flatten().map(|reward| rarity_rank(&reward.rarity)).filter(|rank| *rank != usize::MAX).max()
+    }
+}
+
+// Dates in the export are formatted like "2014-12-17"
+fn parse_date(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()
+}
+
+// Measures how wide text renders, skipping ANSI color escapes like "\x1b[1;36m"
+fn display_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Escape sequences end with their first letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            width += unicode_width::UnicodeWidthChar::width(c).unwrap_or(0);
+        }
+    }
+    width
+}
+
+// Hard-breaks text into pieces at most width columns wide, keeping color escapes with the text they color
+fn split_at_width(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    let mut current_width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        let line = lines.last_mut().expect("there is always a current line");
+        if c == '\x1b' {
+            line.push(c);
+            for c in chars.by_ref() {
+                line.push(c);
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            continue;
+        }
+
+        let char_width = unicode_width::UnicodeWidthChar::width(c).unwrap_or(0);
+        if current_width > 0 && current_width + char_width > width {
+            lines.push(String::from(c));
+            current_width = char_width;
+        } else {
+            line.push(c);
+            current_width += char_width;
+        }
+    }
+    lines
+}
+
+pub fn wrap_text(text: &str, prefix: &str, max_width: usize, indent_after_first: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut current_line = String::from(prefix);
+    let mut current_width = display_width(prefix);
+    let mut line_has_words = false;
+
+    for word in text.split_whitespace() {
+        let word_width = display_width(word);
+
+        // Words follow the prefix or the previous word after a space, but start right after a continuation indent
+        let needs_space = line_has_words || (lines.is_empty() && !current_line.is_empty());
+        let rendered_width = current_width + usize::from(needs_space) + word_width;
+
+        // A word always goes on a line that has none yet, even if it alone is too wide
+        if line_has_words && rendered_width > max_width {
+            lines.push(std::mem::take(&mut current_line));
+            current_line = " ".repeat(indent_after_first);
+            current_width = indent_after_first;
+        } else if needs_space {
+            current_line.push(' ');
+            current_width += 1;
+        }
+        current_line.push_str(word);
+        current_width += word_width;
+        line_has_words = true;
+    }
+    lines.push(current_line);
+
+    lines
+}
+
+// Boxes never get narrower than this, however small the terminal reports itself
+const MIN_BORDER_WIDTH: usize = 20;
+
+fn border_width(term_width: usize) -> usize {
+    // Subtract 2 for the borders
+    term_width.saturating_sub(2).max(MIN_BORDER_WIDTH)
+}
+
+// The glyphs the default format draws its boxes with
+struct BoxChars {
+    top_left: &'static str,
+    top_right: &'static str,
+    bottom_left: &'static str,
+    bottom_right: &'static str,
+    horizontal: &'static str,
+    vertical: &'static str,
+}
+
+const UNICODE_BOX: BoxChars = BoxChars {
+    top_left: "┌",
+    top_right: "┐",
+    bottom_left: "└",
+    bottom_right: "┘",
+    horizontal: "─",
+    vertical: "│",
+};
+
+const ASCII_BOX: BoxChars = BoxChars {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    horizontal: "-",
+    vertical: "|",
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+enum ColorChoice {
+    /// Color the default format when stdout is a terminal and NO_COLOR is unset
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum SortField {
+    Name,
+    Type,
+    Category,
+    IntroducedDate,
+    /// Lowest warframe.market sell price, looked up like --prices
+    Price,
+    /// Rarest reward, Rare first
+    Rarity,
+}
+
+// Items missing the sort field always go last, even when reversed
+fn compare_optional<T: Ord>(a: Option<T>, b: Option<T>, reverse: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if reverse => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+// Stable, so items that compare equal keep their input order
+fn sort_items(items: &mut [Item], field: SortField, reverse: bool, prices: &mut MarketPrices) {
+    // Prices are looked up before sorting, in input order, so the comparator only has to read the cache
+    if field == SortField::Price {
+        for market in items.iter().filter_map(|item| item.warframeMarket.as_ref()) {
+            prices.lowest_sell_price(&market.urlName);
+        }
+    }
+
+    items.sort_by(|a, b| match field {
+        SortField::Name => compare_optional(Some(&a.name), Some(&b.name), reverse),
+        SortField::Type => compare_optional(Some(&a.type_), Some(&b.type_), reverse),
+        SortField::Category => compare_optional(a.category.as_ref(), b.category.as_ref(), reverse),
+        SortField::IntroducedDate => compare_optional(a.introduced_date(), b.introduced_date(), reverse),
+        SortField::Price => compare_optional(prices.cached_price(a), prices.cached_price(b), reverse),
+        SortField::Rarity => {
+            compare_optional(a.best_reward_rarity().map(Reverse), b.best_reward_rarity().map(Reverse), reverse)
+        }
+    });
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Column {
+    Name,
+    UniqueName,
+    Type,
+    Tradable,
+    Category,
+    ProductCategory,
+    Description,
+    IntroducedDate,
+    EstimatedVaultDate,
+}
+
+impl Column {
+    const DEFAULT: [Column; 5] = [Column::Name, Column::UniqueName, Column::Type, Column::Tradable, Column::Category];
+
+    const MARKDOWN_DEFAULT: [Column; 4] = [Column::Name, Column::Type, Column::Category, Column::Tradable];
+
+    const ALL: [Column; 9] = [
+        Column::Name,
+        Column::UniqueName,
+        Column::Type,
+        Column::Tradable,
+        Column::Category,
+        Column::ProductCategory,
+        Column::Description,
+        Column::IntroducedDate,
+        Column::EstimatedVaultDate,
+    ];
+
+    fn from_str(s: &str) -> Option<Column> {
+        Column::ALL.into_iter().find(|column| column.header().eq_ignore_ascii_case(s))
+    }
+
+    fn parse(s: &str) -> std::result::Result<Column, String> {
+        Column::from_str(s.trim()).ok_or_else(|| {
+            let valid: Vec<&str> = Column::ALL.iter().map(|column| column.header()).collect();
+            format!("unknown column '{}' (valid columns: {})", s, valid.join(", "))
+        })
+    }
+
+    fn header(&self) -> &'static str {
+        match self {
+            Column::Name => "name",
+            Column::UniqueName => "uniqueName",
+            Column::Type => "type",
+            Column::Tradable => "tradable",
+            Column::Category => "category",
+            Column::ProductCategory => "productCategory",
+            Column::Description => "description",
+            Column::IntroducedDate => "introducedDate",
+            Column::EstimatedVaultDate => "estimatedVaultDate",
+        }
+    }
+
+    fn value(&self, item: &Item) -> String {
+        match self {
+            Column::Name => item.name.clone(),
+            Column::UniqueName => item.uniqueName.clone(),
+            Column::Type => item.type_.clone(),
+            Column::Tradable => item.tradable.to_string(),
+            Column::Category => item.category.clone().unwrap_or_default(),
+            Column::ProductCategory => item.productCategory.clone().unwrap_or_default(),
+            Column::Description => item.description.clone().unwrap_or_default(),
+            Column::IntroducedDate => item.introduced.as_ref().map(|introduced| introduced.date.clone()).unwrap_or_default(),
+            Column::EstimatedVaultDate => item.estimatedVaultDate.clone().unwrap_or_default(),
+        }
+    }
+}
+
+// Sections of the boxed default format, listed in the order they print by default
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BoxField {
+    Name,
+    UniqueName,
+    Description,
+    Type,
+    Tradable,
+    MarketUrl,
+    Price,
+    Category,
+    ProductCategory,
+    IntroducedDate,
+    EstimatedVaultDate,
+    Components,
+    Patchlogs,
+    Rewards,
+}
+
+impl BoxField {
+    const ALL: [BoxField; 14] = [
+        BoxField::Name,
+        BoxField::UniqueName,
+        BoxField::Description,
+        BoxField::Type,
+        BoxField::Tradable,
+        BoxField::MarketUrl,
+        BoxField::Price,
+        BoxField::Category,
+        BoxField::ProductCategory,
+        BoxField::IntroducedDate,
+        BoxField::EstimatedVaultDate,
+        BoxField::Components,
+        BoxField::Patchlogs,
+        BoxField::Rewards,
+    ];
+
+    fn from_str(s: &str) -> Option<BoxField> {
+        BoxField::ALL.into_iter().find(|field| field.name().eq_ignore_ascii_case(s))
+    }
+
+    fn parse(s: &str) -> std::result::Result<BoxField, String> {
+        BoxField::from_str(s.trim()).ok_or_else(|| {
+            let valid: Vec<&str> = BoxField::ALL.iter().map(|field| field.name()).collect();
+            format!("unknown field '{}' (valid fields: {})", s, valid.join(", "))
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            BoxField::Name => "name",
+            BoxField::UniqueName => "uniqueName",
+            BoxField::Description => "description",
+            BoxField::Type => "type",
+            BoxField::Tradable => "tradable",
+            BoxField::MarketUrl => "marketUrl",
+            BoxField::Price => "price",
+            BoxField::Category => "category",
+            BoxField::ProductCategory => "productCategory",
+            BoxField::IntroducedDate => "introducedDate",
+            BoxField::EstimatedVaultDate => "estimatedVaultDate",
+            BoxField::Components => "components",
+            BoxField::Patchlogs => "patchlogs",
+            BoxField::Rewards => "rewards",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct TomlItems<'a> {
+    items: [&'a Item; 1],
+}
+
+// Holds the state that has to live across items while logging, like the relic dedup set and where output goes
+struct ItemLogger<'a> {
+    out: &'a mut dyn Write,
+    output_format: OutputFormat,
+    has_relic_arg: bool,
+    term_width: usize,
+    wrap: bool,
+    indent: usize,
+    // Only ever asked whether a name was seen, never iterated, so deduplicated names keep their first-seen order
+    unique_items: HashSet<String>,
+    columns: Vec<Column>,
+    pretty: bool,
+    json_items_logged: usize,
+    toml_items_logged: usize,
+    color: bool,
+    unique: bool,
+    grouped: bool,
+    current_group: Option<&'static str>,
+    group_sizes: HashMap<&'static str, usize>,
+    fields: Vec<BoxField>,
+    box_chars: Option<&'static BoxChars>,
+    image_base_url: String,
+    market_base_url: String,
+    show_prices: bool,
+    prices: Option<&'a mut MarketPrices>,
+    chance_format: ChanceFormat,
+    show_patchlog_details: bool,
+    patchlogs_limit: usize,
+}
+
+impl<'a> ItemLogger<'a> {
+    // Writes any header the format starts with right away, so even an empty listing is a complete document
+    fn new(args: &Args, out: &'a mut dyn Write) -> io::Result<ItemLogger<'a>> {
+        let output_format = args.output_format();
+        // "--width 0" turns wrapping off, keeping the default width for the box rules.
+        // Output going to a file with --output isn't shown in the terminal, so its width doesn't apply.
+        let (term_width, wrap) = match args.width {
+            Some(0) => (80, false),
+            Some(width) => (width, true),
+            None if args.output.is_some() => (80, true),
+            None => (dimensions_stdout().unwrap_or((80, 24)).0, true), // Default width: 80, height: 24
+        };
+        if output_format == OutputFormat::Default {
+            match args.width {
+                Some(0) => log_verbose!("info: wrapping disabled by --width 0"),
+                Some(width) => log_verbose!("info: using a width of {} from --width", width),
+                None => log_verbose!("info: using a terminal width of {}", term_width),
+            }
+        }
+
+        let logger = ItemLogger {
+            out,
+            output_format,
+            has_relic_arg: args.has_relic_arg(),
+            term_width,
+            wrap,
+            indent: args.indent,
+            unique_items: HashSet::new(),
+            columns: args.columns(),
+            pretty: args.pretty,
+            json_items_logged: 0,
+            toml_items_logged: 0,
+            color: args.color_enabled(),
+            unique: args.unique,
+            grouped: args.grouped && output_format == OutputFormat::Search,
+            current_group: None,
+            group_sizes: HashMap::new(),
+            fields: args.fields(),
+            box_chars: args.box_chars(),
+            image_base_url: args.image_base_url.clone(),
+            market_base_url: args.market_base.clone(),
+            show_prices: args.prices,
+            prices: None,
+            chance_format: args.chance_format(),
+            show_patchlog_details: args.patchlog_details,
+            patchlogs_limit: args.patchlogs_limit,
+        };
+
+        match output_format {
+            OutputFormat::Csv => {
+                write_csv_record(logger.out, logger.columns.iter().map(|column| column.header()))?;
+            }
+            OutputFormat::Markdown => {
+                write_markdown_row(logger.out, logger.columns.iter().map(|column| column.header().to_string()))?;
+                write_markdown_row(logger.out, logger.columns.iter().map(|_| String::from("---")))?;
+            }
+            // The JSON array is opened here and closed in finish, so items can be written as they arrive
+            OutputFormat::Json => write!(logger.out, "[")?,
+            _ => {}
+        }
+        Ok(logger)
+    }
+
+    // Shares the run's prices when --prices shows them, so ones already looked up, e.g. for "--sort price",
+    // aren't fetched again and new ones are saved to the --price-cache file along with the rest
+    fn with_prices(mut self, prices: &'a mut MarketPrices) -> ItemLogger<'a> {
+        if self.show_prices {
+            self.prices = Some(prices);
+        }
+        self
+    }
+
+    fn log(&mut self, item: &Item) -> io::Result<()> {
+        match self.output_format {
+            OutputFormat::Default => self.log_boxed(item)?,
+            // OutputFormat::Default => {
+            //     println!("Name: {}", item.name);
+            //     println!("UniqueName: {}", item.uniqueName);
+            //     println!("Description: {:?}", item.description);
+            //     println!("Type: {}", item.type_);
+            //     println!("Tradable: {}", item.tradable);
+            //     println!("Category: {:?}", item.category);
+            //     println!("Product Category: {:?}", item.productCategory);
+            //     println!("Introduced Date: {:?}", item.introduced.as_ref().map(|v| &v.date));
+            //     println!("Estimated Vault Date: {:?}", item.estimatedVaultDate);
+
+            //     // Print rewards if available
+            //     if let Some(rewards) = &item.rewards {
+            //         for reward in rewards {
+            //             println!("  - {}", reward.item.name);
+            //         }
+            //     }
+
+            //     println!("---");
+            // }
+            OutputFormat::Search => {
+                if self.has_relic_arg {
+                    let short_name = item.get_relic_short_name();
+                    if self.unique_items.insert(short_name.clone()) {
+                        if self.grouped {
+                            self.log_group_header(item)?;
+                        }
+                        writeln!(self.out, "{}", short_name)?;
+                    }
+                } else if !self.unique || self.unique_items.insert(item.name.clone()) {
+                    writeln!(self.out, "{}", item.name)?;
+                }
+            }
+            // Pretty NDJSON is no longer one object per line, but jq and friends still read it as a stream of objects
+            OutputFormat::Ndjson if self.pretty => {
+                writeln!(self.out, "{}", serde_json::to_string_pretty(item).expect("items always serialize to JSON"))?;
+            }
+            OutputFormat::Ndjson => {
+                writeln!(self.out, "{}", serde_json::to_string(item).expect("items always serialize to JSON"))?;
+            }
+            OutputFormat::Json => {
+                let separator = if self.json_items_logged == 0 { "" } else { "," };
+                if self.pretty {
+                    let json = serde_json::to_string_pretty(item).expect("items always serialize to JSON");
+                    write!(self.out, "{}\n  {}", separator, json.replace('\n', "\n  "))?;
+                } else {
+                    write!(self.out, "{}{}", separator, serde_json::to_string(item).expect("items always serialize to JSON"))?;
+                }
+                self.json_items_logged += 1;
+            }
+            OutputFormat::Line => {
+                writeln!(self.out, "{}\t{}\t{}", item.name, item.type_, item.tradable)?;
+            }
+            // Each item is written as a one-element list, and consecutive lists read back as a single list
+            OutputFormat::Yaml => {
+                write!(self.out, "{}", serde_yaml::to_string(&[item]).expect("items always serialize to YAML"))?;
+            }
+            // Likewise, each "[[items]]" table appends to the same array
+            OutputFormat::Toml => {
+                let separator = if self.toml_items_logged == 0 { "" } else { "\n" };
+                let table = toml::to_string(&TomlItems { items: [item] }).expect("items always serialize to TOML");
+                write!(self.out, "{}{}", separator, table)?;
+                self.toml_items_logged += 1;
+            }
+            OutputFormat::Image => {
+                if let Some(image_name) = &item.imageName {
+                    writeln!(self.out, "{}/{}", self.image_base_url.trim_end_matches('/'), image_name)?;
+                }
+            }
+            OutputFormat::Market => {
+                if let Some(url) = self.market_url(item) {
+                    writeln!(self.out, "{}", url)?;
+                }
+            }
+            OutputFormat::Markdown => {
+                write_markdown_row(self.out, self.columns.iter().map(|column| column.value(item)))?;
+            }
+            OutputFormat::Csv => {
+                write_csv_record(self.out, self.columns.iter().map(|column| column.value(item)))?;
+            }
+        }
+        Ok(())
+    }
+
+    // Without a box, items are separated by a blank line instead
+    fn log_boxed(&mut self, item: &Item) -> io::Result<()> {
+        let Some(box_chars) = self.box_chars else {
+            for field in self.fields.clone() {
+                self.log_box_field(item, field)?;
+            }
+            return writeln!(self.out);
+        };
+
+        let rule = box_chars.horizontal.repeat(border_width(self.term_width));
+        writeln!(self.out, "{}{}{}", box_chars.top_left, rule, box_chars.top_right)?;
+        for field in self.fields.clone() {
+            self.log_box_field(item, field)?;
+        }
+        writeln!(self.out, "{}{}{}", box_chars.bottom_left, rule, box_chars.bottom_right)
+    }
+
+    fn log_box_field(&mut self, item: &Item, field: BoxField) -> io::Result<()> {
+        let wrap_width = self.wrap_width();
+
+        match field {
+            BoxField::Name => self.print_box_field("Name:", &item.name)?,
+            BoxField::UniqueName => self.print_box_field("UniqueName:", &item.uniqueName)?,
+            BoxField::Description => {
+                if let Some(description) = &item.description {
+                    for line in wrap_text(description, &self.label("Description:"), wrap_width, self.indent) {
+                        self.print_box_line(&line)?;
+                    }
+                }
+            }
+            BoxField::Type => self.print_box_field("Type:", &item.type_)?,
+            BoxField::Tradable => self.print_box_field("Tradable:", &item.tradable.to_string())?,
+            BoxField::MarketUrl => {
+                if let Some(url) = self.market_url(item) {
+                    self.print_box_field("Market:", &url)?;
+                }
+            }
+            // Only items with a market entry have a price to look up
+            BoxField::Price => {
+                if let (Some(prices), Some(market)) = (self.prices.as_deref_mut(), &item.warframeMarket) {
+                    let price = match prices.lowest_sell_price(&market.urlName) {
+                        Some(price) => format!("{} platinum", price),
+                        None => String::from("n/a"),
+                    };
+                    self.print_box_field("Price:", &price)?;
+                }
+            }
+            BoxField::Category => {
+                if let Some(category) = &item.category {
+                    self.print_box_field("Category:", category)?;
+                }
+            }
+            BoxField::ProductCategory => {
+                if let Some(product_category) = &item.productCategory {
+                    self.print_box_field("Product Category:", product_category)?;
+                }
+            }
+            BoxField::IntroducedDate => {
+                if let Some(introduced) = &item.introduced {
+                    self.print_box_field("Introduced Date:", &introduced.date)?;
+                }
+            }
+            BoxField::EstimatedVaultDate => {
+                if let Some(vault_date) = &item.estimatedVaultDate {
+                    self.print_box_field("Estimated Vault Date:", vault_date)?;
+                }
+            }
+            BoxField::Components => {
+                if let Some(components) = &item.components {
+                    self.print_box_line(&self.label("Components:"))?;
+                    for component in components {
+                        let tradable = if component.tradable { "tradable" } else { "untradable" };
+                        let text = format!("{} ({})", component.name, tradable);
+                        for line in wrap_text(&text, "  -", wrap_width, 2 + self.indent) {
+                            self.print_box_line(&line)?;
+                        }
+                    }
+                }
+            }
+            BoxField::Patchlogs => {
+                if let Some(patchlogs) = &item.patchlogs {
+                    self.log_patchlogs(patchlogs)?;
+                }
+            }
+            BoxField::Rewards => {
+                for reward in item.rewards_by_chance() {
+                    let text = self.chance_format.describe_with(reward, |rarity| self.rarity(rarity));
+                    for line in wrap_text(&text, "  -", wrap_width, 2 + self.indent) {
+                        self.print_box_line(&line)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn market_url(&self, item: &Item) -> Option<String> {
+        let market = item.warframeMarket.as_ref()?;
+        Some(format!("{}/{}", self.market_base_url.trim_end_matches('/'), market.urlName))
+    }
+
+    fn label(&self, label: &str) -> String {
+        if self.color {
+            label.cyan().bold().to_string()
+        } else {
+            label.to_string()
+        }
+    }
+
+    // Rarities use the in-game bronze, silver and gold
+    fn rarity(&self, rarity: &str) -> String {
+        if !self.color {
+            return rarity.to_string();
+        }
+        match rarity {
+            "Common" => rarity.truecolor(205, 127, 50).to_string(),
+            "Uncommon" => rarity.truecolor(192, 192, 192).to_string(),
+            "Rare" => rarity.truecolor(255, 215, 0).to_string(),
+            _ => rarity.to_string(),
+        }
+    }
+
+    // Wrapped lines have to fit between the "│ " gutter and the " │" right border, if there is one
+    fn wrap_width(&self) -> usize {
+        if !self.wrap {
+            usize::MAX
+        } else if self.box_chars.is_none() {
+            self.term_width.max(MIN_BORDER_WIDTH)
+        } else {
+            border_width(self.term_width).saturating_sub(2)
+        }
+    }
+
+    // A "Label: value" line, wrapped like descriptions so long names don't run past the border
+    fn print_box_field(&mut self, label: &str, value: &str) -> io::Result<()> {
+        for line in wrap_text(value, &self.label(label), self.wrap_width(), self.indent) {
+            self.print_box_line(&line)?;
+        }
+        Ok(())
+    }
+
+    // Pads the line so the right border lines up, measuring display width so wide glyphs don't misalign it.
+    // Words too wide for the box, like uniqueName paths, are broken wherever the width runs out.
+    // Without wrapping there is no fixed right edge, so the border is left off.
+    fn print_box_line(&mut self, text: &str) -> io::Result<()> {
+        let Some(box_chars) = self.box_chars else {
+            return writeln!(self.out, "{}", text);
+        };
+        if !self.wrap {
+            return writeln!(self.out, "{} {}", box_chars.vertical, text);
+        }
+
+        let wrap_width = self.wrap_width();
+        for line in split_at_width(text, wrap_width) {
+            let padding = wrap_width.saturating_sub(display_width(&line));
+            writeln!(self.out, "{} {}{} {}", box_chars.vertical, line, " ".repeat(padding), box_chars.vertical)?;
+        }
+        Ok(())
+    }
+
+    fn log_patchlogs(&mut self, patchlogs: &[Patchlog]) -> io::Result<()> {
+        let wrap_width = self.wrap_width();
+
+        // Dates are ISO 8601 timestamps, so sorting them as strings puts the most recent first
+        let mut patchlogs: Vec<&Patchlog> = patchlogs.iter().collect();
+        patchlogs.sort_by(|a, b| b.date.cmp(&a.date));
+
+        self.print_box_line(&self.label("Patchlogs:"))?;
+        for patchlog in patchlogs.into_iter().take(self.patchlogs_limit) {
+            let date = patchlog.date.get(..10).unwrap_or(&patchlog.date);
+            for line in wrap_text(&patchlog.name, &format!("  {}", date), wrap_width, 2 + self.indent) {
+                self.print_box_line(&line)?;
+            }
+            if self.show_patchlog_details {
+                for (label, text) in [("Additions:", &patchlog.additions), ("Changes:", &patchlog.changes), ("Fixes:", &patchlog.fixes)] {
+                    if text.trim().is_empty() {
+                        continue;
+                    }
+                    for line in wrap_text(text, &format!("    {}", self.label(label)), wrap_width, 4 + self.indent) {
+                        self.print_box_line(&line)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Items are sorted by era before logging, so a header is printed whenever the era changes
+    fn log_group_header(&mut self, item: &Item) -> io::Result<()> {
+        let group = group_name(item);
+        if self.current_group != Some(group) {
+            if self.current_group.is_some() {
+                writeln!(self.out)?;
+            }
+            let size = self.group_sizes.get(group).copied().unwrap_or(0);
+            writeln!(self.out, "== {} ({}) ==", group, size)?;
+            self.current_group = Some(group);
+        }
+        Ok(())
+    }
+
+    // Flushes as well, so output buffered for a file is complete once this returns
+    fn finish(self) -> io::Result<()> {
+        if self.output_format == OutputFormat::Json {
+            if self.pretty && self.json_items_logged > 0 {
+                writeln!(self.out, "\n]")?;
+            } else {
+                writeln!(self.out, "]")?;
+            }
+        }
+        self.out.flush()
+    }
+}
+
+// Pipes are escaped so they don't end the cell early, and line breaks are collapsed so they don't end the row
+fn write_markdown_row(out: &mut dyn Write, cells: impl Iterator<Item = String>) -> io::Result<()> {
+    let cells: Vec<String> = cells
+        .map(|cell| cell.replace('|', "\\|").split_whitespace().collect::<Vec<&str>>().join(" "))
+        .collect();
+    writeln!(out, "| {} |", cells.join(" | "))
+}
+
+// Each record gets its own csv writer, so records go straight to the logger's output in order
+fn write_csv_record<T: AsRef<[u8]>>(out: &mut dyn Write, record: impl IntoIterator<Item = T>) -> io::Result<()> {
+    let mut writer = csv::Writer::from_writer(out);
+    writer.write_record(record)?;
+    writer.flush()
+}
+
+fn group_name(item: &Item) -> &'static str {
+    item.relic_type().map_or("Other", |relic_type| relic_type.name())
+}
+
+fn log_items(mut items: Vec<Item>, mut logger: ItemLogger) -> io::Result<()> {
+    // Stable sort so each era's relics keep their existing order, with non-relics last
+    if logger.grouped {
+        items.sort_by(|a, b| compare_optional(a.relic_type(), b.relic_type(), false));
+
+        // Headers count the lines printed under them, and refinements of one relic share a line
+        let mut short_names = HashSet::new();
+        for item in &items {
+            if short_names.insert(item.get_relic_short_name()) {
+                *logger.group_sizes.entry(group_name(item)).or_insert(0) += 1;
+            }
+        }
+    }
+    for item in items {
+        logger.log(&item)?;
+    }
+    logger.finish()
+}
+
+// Lets the user pick one item by name, then prints only that one. Pressing Esc prints an empty listing.
+fn select_and_log_item(mut items: Vec<Item>, logger: ItemLogger) -> std::result::Result<(), RunError> {
+    if items.is_empty() {
+        return Ok(log_items(items, logger)?);
+    }
+
+    let selection = FuzzySelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Item")
+        .items(items.iter().map(|item| &item.name))
+        .default(0)
+        .interact_opt();
+    match selection {
+        Ok(Some(index)) => Ok(log_items(vec![items.swap_remove(index)], logger)?),
+        Ok(None) => Ok(log_items(Vec::new(), logger)?),
+        Err(err) => Err(RunError::Interactive(err)),
+    }
+}
+
+// Matches "Lith A1" against every refinement ("Lith A1 Intact", "Lith A1 Radiant", ...), or one full name
+fn item_is_relic_named(item: &Item, relic_name: &str, relic_type_label: &str) -> bool {
+    item.is_relic(relic_type_label)
+        && (item.get_relic_short_name().eq_ignore_ascii_case(relic_name) || item.name.eq_ignore_ascii_case(relic_name))
+}
+
+fn log_drop_tables(
+    out: &mut dyn Write,
+    items: &[Item],
+    relic_name: &str,
+    relic_type_label: &str,
+    chance_format: ChanceFormat,
+) -> std::result::Result<(), RunError> {
+    let relics: Vec<&Item> =
+        items.iter().filter(|item| item_is_relic_named(item, relic_name, relic_type_label)).collect();
+    if relics.is_empty() {
+        return Err(RunError::NoRelicNamed(relic_name.to_string()));
+    }
+
+    for (i, relic) in relics.iter().enumerate() {
+        if i > 0 {
+            writeln!(out)?;
+        }
+        writeln!(out, "{}", relic.name)?;
+        for reward in relic.rewards_by_chance() {
+            writeln!(out, "  - {}", chance_format.describe(reward))?;
+        }
+    }
+    Ok(())
+}
+
+// The export repeats the same chances for every refinement, so each relic is only listed once per reward
+fn reward_sources<'a>(items: &'a [Item], query: &SearchQuery, relic_type_label: &str) -> Vec<(String, &'a Reward)> {
+    let mut seen: HashSet<(String, &str)> = HashSet::new();
+    let mut sources: Vec<(String, &Reward)> = Vec::new();
+    for relic in items.iter().filter(|item| item.is_relic(relic_type_label)) {
+        for reward in relic.rewards.iter().flatten().filter(|reward| query.matches(&reward.item.name)) {
+            let short_name = relic.get_relic_short_name();
+            if seen.insert((short_name.clone(), &reward.item.name)) {
+                sources.push((short_name, reward));
+            }
+        }
+    }
+    sources.sort_by(|a, b| b.1.chance.total_cmp(&a.1.chance));
+    sources
+}
+
+fn log_reward_sources(
+    out: &mut dyn Write,
+    items: &[Item],
+    query: &SearchQuery,
+    relic_type_label: &str,
+    chance_format: ChanceFormat,
+) -> std::result::Result<(), RunError> {
+    let sources = reward_sources(items, query, relic_type_label);
+    if sources.is_empty() {
+        return Err(RunError::NoRelicDrops(query.term.clone()));
+    }
+
+    for (short_name, reward) in sources {
+        writeln!(out, "{} {}", short_name, chance_format.describe(reward))?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum StatsField {
+    Type,
+    Category,
+}
+
+// Prints how many items share each value of the field, most common first
+fn log_stats(out: &mut dyn Write, items: &[Item], field: StatsField) -> io::Result<()> {
+    let mut tally: HashMap<&str, usize> = HashMap::new();
+    for item in items {
+        let value = match field {
+            StatsField::Type => item.type_.as_str(),
+            StatsField::Category => item.category.as_deref().unwrap_or("(none)"),
+        };
+        *tally.entry(value).or_insert(0) += 1;
+    }
+
+    let mut tally: Vec<(&str, usize)> = tally.into_iter().collect();
+    tally.sort_by(|(a_value, a_count), (b_value, b_count)| b_count.cmp(a_count).then_with(|| a_value.cmp(b_value)));
+    for (value, count) in tally {
+        writeln!(out, "{}: {}", value, count)?;
+    }
+    Ok(())
+}
+
+// Top-level fields whose values differ, compared as JSON so nested rewards and patchlogs count as one field each
+fn changed_fields(old_item: &Item, new_item: &Item) -> Vec<String> {
+    let to_object = |item: &Item| match serde_json::to_value(item).expect("items always serialize to JSON") {
+        serde_json::Value::Object(fields) => fields,
+        _ => serde_json::Map::new(),
+    };
+    let (old_fields, new_fields) = (to_object(old_item), to_object(new_item));
+    let names: BTreeSet<&String> = old_fields.keys().chain(new_fields.keys()).collect();
+    names.into_iter().filter(|name| old_fields.get(*name) != new_fields.get(*name)).cloned().collect()
+}
+
+// Prints "+" for items only in the new export, "-" for items only in the old one and "~" for changed items,
+// matching them up by uniqueName
+fn log_diff(out: &mut dyn Write, old_items: &[Item], new_items: &[Item]) -> io::Result<()> {
+    let old_by_unique_name: HashMap<&str, &Item> = old_items.iter().map(|item| (item.uniqueName.as_str(), item)).collect();
+    let new_unique_names: HashSet<&str> = new_items.iter().map(|item| item.uniqueName.as_str()).collect();
+    let (mut added_count, mut removed_count, mut changed_count) = (0, 0, 0);
+
+    for item in new_items.iter().filter(|item| !old_by_unique_name.contains_key(item.uniqueName.as_str())) {
+        writeln!(out, "+ {} ({})", item.name, item.uniqueName)?;
+        added_count += 1;
+    }
+    for item in old_items.iter().filter(|item| !new_unique_names.contains(item.uniqueName.as_str())) {
+        writeln!(out, "- {} ({})", item.name, item.uniqueName)?;
+        removed_count += 1;
+    }
+    for new_item in new_items {
+        let Some(old_item) = old_by_unique_name.get(new_item.uniqueName.as_str()) else {
+            continue;
+        };
+        let fields = changed_fields(old_item, new_item);
+        if !fields.is_empty() {
+            writeln!(out, "~ {} ({}): {}", new_item.name, new_item.uniqueName, fields.join(", "))?;
+            changed_count += 1;
+        }
+    }
+
+    log_verbose!("info: {} added, {} removed, {} changed", added_count, removed_count, changed_count);
+    Ok(())
+}
+
+// Lower is more likely to drop; unknown rarities sort after the known ones
+fn rarity_rank(rarity: &str) -> usize {
+    ["Common", "Uncommon", "Rare"].iter().position(|known| known.eq_ignore_ascii_case(rarity)).unwrap_or(usize::MAX)
+}
+
+// Lists every reward of the matched relics once, with how many relics drop it and the most likely rarity it drops at.
+// Refinements of the same relic only count once.
+fn log_rewards_summary(out: &mut dyn Write, items: &[Item], relic_type_label: &str) -> io::Result<()> {
+    let mut summary: HashMap<&str, (HashSet<String>, &str)> = HashMap::new();
+    for relic in items.iter().filter(|item| item.is_relic(relic_type_label)) {
+        for reward in relic.rewards.iter().flatten() {
+            let (relics, most_common_rarity) =
+                summary.entry(&reward.item.name).or_insert_with(|| (HashSet::new(), &reward.rarity));
+            relics.insert(relic.get_relic_short_name());
+            if rarity_rank(&reward.rarity) < rarity_rank(most_common_rarity) {
+                *most_common_rarity = &reward.rarity;
+            }
+        }
+    }
+
+    let mut summary: Vec<(&str, usize, &str)> =
+        summary.into_iter().map(|(name, (relics, most_common_rarity))| (name, relics.len(), most_common_rarity)).collect();
+    summary.sort_by(|(a_name, a_count, _), (b_name, b_count, _)| b_count.cmp(a_count).then_with(|| a_name.cmp(b_name)));
+    for (name, relic_count, most_common_rarity) in summary {
+        writeln!(out, "{}: {} relic(s), mostly {}", name, relic_count, most_common_rarity)?;
+    }
+    Ok(())
+}
+
+// Reports relics whose reward chances don't add up to 100%, failing if there were any
+fn validate_reward_chances(items: &[Item], tolerance: f64, relic_type_label: &str) -> std::result::Result<(), RunError> {
+    // Some relics, like the generic "Lith Relic", list no rewards at all, so there's nothing to check
+    let relics: Vec<&Item> = items
+        .iter()
+        .filter(|item| item.is_relic(relic_type_label) && item.rewards.as_ref().is_some_and(|rewards| !rewards.is_empty()))
+        .collect();
+    let mut invalid_count = 0;
+    for relic in &relics {
+        let total: f64 = relic.rewards.iter().flatten().map(|reward| reward.chance).sum();
+        if (total - 100.0).abs() > tolerance {
+            eprintln!("invalid: {} reward chances add up to {:.2}%", relic.name, total);
+            invalid_count += 1;
+        }
+    }
+
+    log_verbose!("info: checked {} relic(s), {} invalid", relics.len(), invalid_count);
+    if invalid_count > 0 {
+        return Err(RunError::InvalidRewardChances);
+    }
+    Ok(())
+}
+
+fn item_is_relic_of_type(item: &Item, relic_types: &[RelicType], relic_type_label: &str) -> bool {
+    // Filter logic: check if the item's type is the one relics are labelled with, "Relic" by default
+    let is_relic = item.is_relic(relic_type_label);
+
+    // println!("type: {:?}, is_relic: {:?}", item.type_, is_relic);
+    // If relic types were provided, additionally check the item's era against them.
+    // The uniqueName is a path like "/Lotus/Types/Game/Projections/T1VoidProjection...", so it can't be prefix matched.
+    // If no relic type was provided, always consider it a match
+    let matches_relic_type =
+        relic_types.is_empty() || item.relic_type().is_some_and(|relic_type| relic_types.contains(&relic_type));
+
+    // Return true if both conditions are met
+    is_relic && matches_relic_type
+}
+
+// Without "--relic" every item passes; "--relic" alone keeps relics of any era
+pub fn filter_items_by_relic_type<'a>(
+    items: impl Iterator<Item = Item> + 'a,
+    relic_types: Option<&'a [RelicType]>,
+    relic_type_label: &'a str,
+) -> impl Iterator<Item = Item> + 'a {
+    items.filter(move |item| {
+        relic_types.is_none_or(|relic_types| item_is_relic_of_type(item, relic_types, relic_type_label))
+    })
+}
+
+// Relics whose names don't say their refinement are kept, since there's nothing to filter them by
+fn filter_items_by_refinement<'a>(
+    items: impl Iterator<Item = Item> + 'a,
+    refinements: &'a [Refinement],
+) -> impl Iterator<Item = Item> + 'a {
+    items.filter(move |item| {
+        refinements.is_empty() || item.refinement().is_none_or(|refinement| refinements.contains(&refinement))
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchMode {
+    Contains,
+    Prefix,
+    Word,
+}
+
+impl SearchMode {
+    fn matches(&self, haystack: &str, term: &str) -> bool {
+        match self {
+            SearchMode::Contains => haystack.contains(term),
+            SearchMode::Prefix => haystack.starts_with(term),
+            SearchMode::Word => contains_words(haystack, term),
+        }
+    }
+}
+
+fn words(text: &str) -> Vec<&str> {
+    text.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()).collect()
+}
+
+// True if the words of term appear consecutively in haystack, so "prime" matches "Soma Prime" but not "Primed Chamber"
+fn contains_words(haystack: &str, term: &str) -> bool {
+    let term_words = words(term);
+    !term_words.is_empty() && words(haystack).windows(term_words.len()).any(|window| window == term_words.as_slice())
+}
+
+// Trims and collapses runs of whitespace, so terms pasted from fzf with stray spaces still match
+fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+// Fields a "field:term" search can be scoped to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchField {
+    Name,
+    UniqueName,
+    Type,
+    Category,
+    ProductCategory,
+    Description,
+}
+
+impl SearchField {
+    const ALL: [SearchField; 6] = [
+        SearchField::Name,
+        SearchField::UniqueName,
+        SearchField::Type,
+        SearchField::Category,
+        SearchField::ProductCategory,
+        SearchField::Description,
+    ];
+
+    fn from_str(s: &str) -> Option<SearchField> {
+        SearchField::ALL.into_iter().find(|field| field.name().eq_ignore_ascii_case(s))
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            SearchField::Name => "name",
+            SearchField::UniqueName => "uniqueName",
+            SearchField::Type => "type",
+            SearchField::Category => "category",
+            SearchField::ProductCategory => "productCategory",
+            SearchField::Description => "description",
+        }
+    }
+
+    fn value<'a>(&self, item: &'a Item) -> Option<&'a str> {
+        match self {
+            SearchField::Name => Some(&item.name),
+            SearchField::UniqueName => Some(&item.uniqueName),
+            SearchField::Type => Some(&item.type_),
+            SearchField::Category => item.category.as_deref(),
+            SearchField::ProductCategory => item.productCategory.as_deref(),
+            SearchField::Description => item.description.as_deref(),
+        }
+    }
+
+    // Splits "type:Warframe" into the field and the term. Anything else before a colon is part of the term, so names
+    // like "Gradivus: Loyalty Emblem" are still searched as they are. Only a word that looks like a field, with no
+    // space after the colon like "typo:Warframe", is rejected.
+    fn split(term: &str) -> std::result::Result<(Option<SearchField>, &str), String> {
+        let Some((prefix, rest)) = term.split_once(':') else {
+            return Ok((None, term));
+        };
+        if let Some(field) = SearchField::from_str(prefix) {
+            return Ok((Some(field), rest));
+        }
+
+        let looks_like_field = !prefix.is_empty() && prefix.chars().all(|c| c.is_ascii_alphabetic());
+        if looks_like_field && !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+            let valid: Vec<&str> = SearchField::ALL.iter().map(|field| field.name()).collect();
+            return Err(format!("unknown search field '{}' (valid fields: {})", prefix, valid.join(", ")));
+        }
+        Ok((None, term))
+    }
+}
+
+// Checks the field prefix of --search while parsing arguments, keeping the term as it was given
+fn parse_search_term(term: &str) -> std::result::Result<String, String> {
+    SearchField::split(term).map(|_| term.to_string())
+}
+
+pub struct SearchQuery {
+    term: String,
+    mode: SearchMode,
+    case_sensitive: bool,
+    // Set for "field:term" searches; otherwise both name and uniqueName are searched
+    field: Option<SearchField>,
+    // Set by --search-aliases, so names and uniqueNames also match the aliases of the update that introduced the item
+    aliases: bool,
+    // Set when the term names a relic, so "Lith A1" matches every refinement of it but not "Lith A10"
+    relic_short_name: Option<String>,
+    // The type relic short names are looked up among, see --relic-type-label
+    relic_type_label: String,
+}
+
+impl SearchQuery {
+    pub fn new(term: &str, mode: SearchMode, case_sensitive: bool) -> SearchQuery {
+        let relic_short_name = relic_short_name_of(term);
+        let term = normalize_whitespace(term);
+        let term = if case_sensitive { term } else { term.to_lowercase() };
+        SearchQuery {
+            term,
+            mode,
+            case_sensitive,
+            field: None,
+            aliases: false,
+            relic_short_name,
+            relic_type_label: String::from("Relic"),
+        }
+    }
+
+    // Like new, but reading a "field:term" prefix
+    pub fn scoped(term: &str, mode: SearchMode, case_sensitive: bool) -> std::result::Result<SearchQuery, String> {
+        let (field, term) = SearchField::split(term)?;
+        let mut query = SearchQuery::new(term, mode, case_sensitive);
+        if field.is_some_and(|field| field != SearchField::Name) {
+            query.relic_short_name = None;
+        }
+        query.field = field;
+        Ok(query)
+    }
+
+    pub fn matches(&self, haystack: &str) -> bool {
+        let haystack = normalize_whitespace(haystack);
+        if self.case_sensitive {
+            self.mode.matches(&haystack, &self.term)
+        } else {
+            self.mode.matches(&haystack.to_lowercase(), &self.term)
+        }
+    }
+}
+
+// Words that follow the era and code in relic names
+const RELIC_DESCRIPTORS: [&str; 5] = ["relic", "intact", "exceptional", "flawless", "radiant"];
+
+fn is_relic_descriptor(word: &str) -> bool {
+    RELIC_DESCRIPTORS.iter().any(|descriptor| word.eq_ignore_ascii_case(descriptor))
+}
+
+// Reads terms like "Lith S12", "lith s12 relic" or "Lith S12 Radiant" as the relic short name "Lith S12",
+// ignoring the trailing descriptors that relic names picked from fzf or the game tend to carry
+fn relic_short_name_of(term: &str) -> Option<String> {
+    let words: Vec<&str> = term.split_whitespace().collect();
+    let [era, code, descriptors @ ..] = words.as_slice() else {
+        return None;
+    };
+    RelicType::from_str(era)?;
+    let is_code = code.chars().all(|c| c.is_ascii_alphanumeric()) && code.chars().any(|c| c.is_ascii_digit());
+    let has_only_descriptors = descriptors.iter().all(|word| is_relic_descriptor(word));
+    (is_code && has_only_descriptors).then(|| format!("{} {}", era, code))
+}
+
+fn item_matches_search_term(item: &Item, query: &SearchQuery) -> bool {
+    if let Some(relic_short_name) = &query.relic_short_name {
+        return item.is_relic(&query.relic_type_label) && item.get_relic_short_name().eq_ignore_ascii_case(relic_short_name);
+    }
+    match query.field {
+        Some(field) => field.value(item).is_some_and(|value| query.matches(value)),
+        None => query.matches(&item.name) || query.matches(&item.uniqueName) || item_matches_alias(item, query),
+    }
+}
+
+// Items without an introduction never match on aliases
+fn item_matches_alias(item: &Item, query: &SearchQuery) -> bool {
+    query.aliases
+        && item.introduced.as_ref().is_some_and(|introduced| introduced.aliases.iter().any(|alias| query.matches(alias)))
+}
+
+// Parsed items kept around to answer many searches, with the names normalized and lowercased once up front
+struct Index {
+    entries: Vec<IndexEntry>,
+}
+
+struct IndexEntry {
+    item: Item,
+    normalized_name: String,
+    lowercase_name: String,
+    lowercase_unique_name: String,
+}
+
+impl Index {
+    fn new(items: Vec<Item>) -> Index {
+        let entries = items
+            .into_iter()
+            .map(|item| IndexEntry {
+                normalized_name: normalize_whitespace(&item.name),
+                lowercase_name: normalize_whitespace(&item.name).to_lowercase(),
+                lowercase_unique_name: item.uniqueName.to_lowercase(),
+                item,
+            })
+            .collect();
+        Index { entries }
+    }
+
+    // Same matching as "--search", in index order
+    fn search<'a>(&'a self, query: &'a SearchQuery) -> impl Iterator<Item = &'a Item> + 'a {
+        self.entries.iter().filter(move |entry| {
+            if query.relic_short_name.is_some() || query.field.is_some() || query.aliases {
+                return item_matches_search_term(&entry.item, query);
+            }
+            let (name, unique_name) = if query.case_sensitive {
+                (&entry.normalized_name, &entry.item.uniqueName)
+            } else {
+                (&entry.lowercase_name, &entry.lowercase_unique_name)
+            };
+            query.mode.matches(name, &query.term) || query.mode.matches(unique_name, &query.term)
+        }).map(|entry| &entry.item)
+    }
+}
+
+// Each query gets its own logger, so formats like JSON produce one complete document per query.
+// Only the default and names formats get a "== term ==" header, since it would break the documents of the others.
+fn answer_queries(out: &mut dyn Write, index: &Index, args: &Args, prices: &mut MarketPrices) -> io::Result<()> {
+    let limit = args.limit();
+    let has_headers = matches!(args.output_format(), OutputFormat::Default | OutputFormat::Search);
+    let mut queries_answered = 0;
+    for line in io::stdin().lock().lines() {
+        let line = line.unwrap_or_else(|err| {
+            eprintln!("error: failed to read query from stdin: {}", err);
+            process::exit(EXIT_IO);
+        });
+        let term = line.trim();
+        if term.is_empty() {
+            continue;
+        }
+
+        let query = match args.search_query_for(term) {
+            Ok(query) => query,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                continue;
+            }
+        };
+        let matches = index.search(&query).take(limit);
+        if args.count {
+            writeln!(out, "{}", matches.count())?;
+        } else if !args.no_output {
+            if has_headers {
+                if queries_answered > 0 {
+                    writeln!(out)?;
+                }
+                writeln!(out, "== {} ==", term)?;
+            }
+            let mut logger = ItemLogger::new(args, out)?.with_prices(prices);
+            for item in matches {
+                logger.log(item)?;
+            }
+            logger.finish()?;
+        }
+        queries_answered += 1;
+    }
+    out.flush()
+}
+
+pub fn filter_items_by_search_term(items: impl Iterator<Item = Item>, query: Option<SearchQuery>) -> impl Iterator<Item = Item> {
+    items.filter(move |item| query.as_ref().is_none_or(|query| item_matches_search_term(item, query)))
+}
+
+// Items without a description never match
+fn filter_items_by_description(items: impl Iterator<Item = Item>, query: Option<SearchQuery>) -> impl Iterator<Item = Item> {
+    items.filter(move |item| {
+        query.as_ref().is_none_or(|query| item.description.as_ref().is_some_and(|description| query.matches(description)))
+    })
+}
+
+fn filter_items_by_exclusions(items: impl Iterator<Item = Item>, exclusions: Vec<SearchQuery>) -> impl Iterator<Item = Item> {
+    items.filter(move |item| !exclusions.iter().any(|query| item_matches_search_term(item, query)))
+}
+
+fn filter_items_by_regex<'a>(
+    items: impl Iterator<Item = Item> + 'a,
+    regex: Option<&'a Regex>,
+    include_unique_name: bool,
+) -> impl Iterator<Item = Item> + 'a {
+    items.filter(move |item| {
+        regex.is_none_or(|regex| regex.is_match(&item.name) || (include_unique_name && regex.is_match(&item.uniqueName)))
+    })
+}
+
+fn filter_items_by_tradable(items: impl Iterator<Item = Item>, tradable: Option<bool>) -> impl Iterator<Item = Item> {
+    items.filter(move |item| tradable.is_none_or(|tradable| item.tradable == tradable))
+}
+
+fn filter_items_by_type<'a>(items: impl Iterator<Item = Item> + 'a, type_: Option<&'a str>) -> impl Iterator<Item = Item> + 'a {
+    items.filter(move |item| type_.is_none_or(|type_| item.type_.eq_ignore_ascii_case(type_)))
+}
+
+// Compares whole "/"-separated components, so "Primary" doesn't match ".../PrimaryArcanes/..."
+fn unique_name_has_path_segment(unique_name: &str, segment: &[&str]) -> bool {
+    let components: Vec<&str> = unique_name.split('/').filter(|component| !component.is_empty()).collect();
+    !segment.is_empty()
+        && components.windows(segment.len()).any(|window| {
+            window.iter().zip(segment).all(|(component, wanted)| component.eq_ignore_ascii_case(wanted))
+        })
+}
+
+fn filter_items_by_path<'a>(items: impl Iterator<Item = Item> + 'a, path: Option<&'a str>) -> impl Iterator<Item = Item> + 'a {
+    let segment: Option<Vec<&str>> =
+        path.map(|path| path.split('/').filter(|component| !component.is_empty()).collect());
+    items.filter(move |item| {
+        segment.as_ref().is_none_or(|segment| unique_name_has_path_segment(&item.uniqueName, segment))
+    })
+}
+
+// Items without an introduced entry never match while any of these filters is active
+fn filter_items_by_introduced<'a>(
+    items: impl Iterator<Item = Item> + 'a,
+    update_name: Option<&'a str>,
+    after: Option<NaiveDate>,
+    before: Option<NaiveDate>,
+) -> impl Iterator<Item = Item> + 'a {
+    let active = update_name.is_some() || after.is_some() || before.is_some();
+
+    items.filter(move |item| {
+        if !active {
+            return true;
+        }
+        let Some(introduced) = &item.introduced else {
+            return false;
+        };
+        let matches_name = update_name.is_none_or(|name| introduced.name.eq_ignore_ascii_case(name));
+        let matches_dates = match item.introduced_date() {
+            Some(date) => after.is_none_or(|after| date > after) && before.is_none_or(|before| date < before),
+            None => after.is_none() && before.is_none(),
+        };
+        matches_name && matches_dates
+    })
+}
+
+// Items without an estimated vault date never match while either filter is active
+fn filter_items_by_vault_date(
+    items: impl Iterator<Item = Item>,
+    vaulting_before: Option<NaiveDate>,
+    vaulted: bool,
+    today: NaiveDate,
+) -> impl Iterator<Item = Item> {
+    let active = vaulting_before.is_some() || vaulted;
+
+    items.filter(move |item| {
+        if !active {
+            return true;
+        }
+        let Some(vault_date) = item.estimated_vault_date() else {
+            return false;
+        };
+        vaulting_before.is_none_or(|before| vault_date < before) && (!vaulted || vault_date < today)
+    })
+}
+
+// Some(true) keeps vaulted items and Some(false) the ones still in rotation, as judged by Item::is_vaulted
+fn filter_items_by_vault_state(
+    items: impl Iterator<Item = Item>,
+    vaulted: Option<bool>,
+    today: NaiveDate,
+) -> impl Iterator<Item = Item> {
+    items.filter(move |item| vaulted.is_none_or(|vaulted| item.is_vaulted(today) == vaulted))
+}
+
+fn filter_items_on_market(items: impl Iterator<Item = Item>, on_market: bool) -> impl Iterator<Item = Item> {
+    items.filter(move |item| !on_market || item.warframeMarket.is_some())
+}
+
+// Every tag has to be present, so repeating "--tag" narrows the results; items without tags never match
+fn filter_items_by_tags(items: impl Iterator<Item = Item>, tags: Vec<String>) -> impl Iterator<Item = Item> {
+    items.filter(move |item| {
+        tags.iter().all(|tag| item.tags.iter().flatten().any(|item_tag| item_tag.eq_ignore_ascii_case(tag)))
+    })
+}
+
+// Items missing the field being checked never match
+fn filter_items_by_mastery(items: impl Iterator<Item = Item>, masterable: bool, mr_max: Option<i64>) -> impl Iterator<Item = Item> {
+    items.filter(move |item| {
+        (!masterable || item.masterable == Some(true))
+            && mr_max.is_none_or(|mr_max| item.masteryReq.is_some_and(|mastery_req| mastery_req <= mr_max))
+    })
+}
+
+// Keeps items with at least one reward satisfying both conditions, so items without rewards never match
+fn filter_items_by_reward<'a>(
+    items: impl Iterator<Item = Item> + 'a,
+    rarity: Option<&'a str>,
+    min_chance: Option<f64>,
+) -> impl Iterator<Item = Item> + 'a {
+    items.filter(move |item| {
+        (rarity.is_none() && min_chance.is_none())
+            || item.rewards.iter().flatten().any(|reward| {
+                rarity.is_none_or(|rarity| reward.rarity.eq_ignore_ascii_case(rarity))
+                    && min_chance.is_none_or(|min_chance| reward.chance >= min_chance)
+            })
+    })
+}
+
+// Items missing the field never match
+fn optional_field_equals_ignore_case(field: &Option<String>, value: &str) -> bool {
+    field.as_ref().is_some_and(|field| field.eq_ignore_ascii_case(value))
+}
+
+fn filter_items_by_category<'a>(items: impl Iterator<Item = Item> + 'a, category: Option<&'a str>) -> impl Iterator<Item = Item> + 'a {
+    items.filter(move |item| category.is_none_or(|category| optional_field_equals_ignore_case(&item.category, category)))
+}
+
+fn filter_items_by_product_category<'a>(
+    items: impl Iterator<Item = Item> + 'a,
+    product_category: Option<&'a str>,
+) -> impl Iterator<Item = Item> + 'a {
+    items.filter(move |item| {
+        product_category.is_none_or(|product_category| optional_field_equals_ignore_case(&item.productCategory, product_category))
+    })
+}
+
+// Keeps items whose name fuzzily matches the query with at least min_score, ranked best match first.
+// Ranking needs every match up front, so this is the one stage that collects.
+fn filter_items_by_fuzzy_query<'a>(
+    items: impl Iterator<Item = Item> + 'a,
+    query: Option<&str>,
+    min_score: i64,
+) -> Box<dyn Iterator<Item = Item> + 'a> {
+    match query {
+        Some(query) => {
+            let matcher = SkimMatcherV2::default();
+            let mut scored_items: Vec<(i64, Item)> = items.filter_map(|item| {
+                matcher.fuzzy_match(&item.name, query)
+                    .filter(|score| *score >= min_score)
+                    .map(|score| (score, item))
+            }).collect();
+
+            // Stable sort, so equally scored items keep their input order
+            scored_items.sort_by(|(a, _), (b, _)| b.cmp(a));
+            Box::new(scored_items.into_iter().map(|(_, item)| item))
+        },
+        None => Box::new(items),
+    }
+}
+
+// Counts how many items made it through each filter stage, for --verbose
+#[derive(Default)]
+struct StageCounts {
+    counts: RefCell<Vec<(&'static str, usize)>>,
+}
+
+impl StageCounts {
+    // Counts accumulate across calls, so streamed chunks add up to one total per stage.
+    // Boxing keeps each stage's type from nesting every earlier stage, which would blow up compile times.
+    fn track<'a>(&'a self, items: impl Iterator<Item = Item> + 'a, stage: &'static str) -> Box<dyn Iterator<Item = Item> + 'a> {
+        let mut counts = self.counts.borrow_mut();
+        let index = counts.iter().position(|(name, _)| *name == stage).unwrap_or_else(|| {
+            counts.push((stage, 0));
+            counts.len() - 1
+        });
+        Box::new(items.inspect(move |_| self.counts.borrow_mut()[index].1 += 1))
+    }
+
+    // Adds the counts of a chunk filtered on its own, such as with --parallel
+    fn merge(&self, other: StageCounts) {
+        let mut counts = self.counts.borrow_mut();
+        for (stage, count) in other.counts.into_inner() {
+            match counts.iter_mut().find(|(name, _)| *name == stage) {
+                Some((_, total)) => *total += count,
+                None => counts.push((stage, count)),
+            }
+        }
+    }
+
+    fn report(&self) {
+        for (stage, count) in self.counts.borrow().iter() {
+            log_verbose!("info: {} item(s) left after the {} filter", count, stage);
+        }
+    }
+}
+
+// Each stage wraps the previous one lazily, so items pass through the whole pipeline one at a time
+fn filter_items<'a>(
+    items: impl Iterator<Item = Item> + 'a,
+    args: &'a Args,
+    stage_counts: &'a StageCounts,
+) -> impl Iterator<Item = Item> + 'a {
+    // Filter items by relic type if provided
+    let filtered_items =
+        stage_counts.track(filter_items_by_relic_type(items, args.relic_types(), &args.relic_type_label), "relic");
+
+    // Filter relics by refinement if provided
+    let filtered_items =
+        stage_counts.track(filter_items_by_refinement(filtered_items, &args.refinement), "refinement");
+
+    // Filter items by type if provided
+    let filtered_items = stage_counts.track(filter_items_by_type(filtered_items, args.type_.as_deref()), "type");
+
+    // Filter items by uniqueName path segment if provided
+    let filtered_items = stage_counts.track(filter_items_by_path(filtered_items, args.path.as_deref()), "path");
+
+    // Filter items by search term if provided
+    let filtered_items = stage_counts.track(filter_items_by_search_term(filtered_items, args.search_query()), "search");
+
+    // Filter items by description text if provided
+    let filtered_items =
+        stage_counts.track(filter_items_by_description(filtered_items, args.description_query()), "description");
+
+    // Filter items by regex if provided
+    let filtered_items = stage_counts.track(
+        filter_items_by_regex(filtered_items, args.regex.as_ref(), args.regex_unique_name),
+        "regex",
+    );
+
+    // Filter items by tradable status if requested
+    let filtered_items = stage_counts.track(filter_items_by_tradable(filtered_items, args.tradable()), "tradable");
+
+    // Filter items by category and product category if provided
+    let filtered_items = stage_counts.track(filter_items_by_category(filtered_items, args.category.as_deref()), "category");
+    let filtered_items = stage_counts.track(
+        filter_items_by_product_category(filtered_items, args.product_category.as_deref()),
+        "product category",
+    );
+
+    // Filter items by the update that introduced them if requested
+    let filtered_items = stage_counts.track(
+        filter_items_by_introduced(
+            filtered_items,
+            args.introduced.as_deref(),
+            args.introduced_after,
+            args.introduced_before,
+        ),
+        "introduced",
+    );
+
+    // Filter items by estimated vault date if requested
+    let filtered_items = stage_counts.track(
+        filter_items_by_vault_date(
+            filtered_items,
+            args.vaulting_before,
+            args.vaulted,
+            Local::now().date_naive(),
+        ),
+        "vault date",
+    );
+
+    // Filter relics by whether they are vaulted if requested
+    let filtered_items = stage_counts.track(
+        filter_items_by_vault_state(filtered_items, args.vault_state(), Local::now().date_naive()),
+        "vault state",
+    );
+
+    // Filter items to those listed on Warframe.Market if requested
+    let filtered_items = stage_counts.track(filter_items_on_market(filtered_items, args.on_market), "on-market");
+
+    // Filter items by tags if provided
+    let filtered_items = stage_counts.track(filter_items_by_tags(filtered_items, args.tags.clone()), "tag");
+
+    // Filter items by mastery if requested
+    let filtered_items =
+        stage_counts.track(filter_items_by_mastery(filtered_items, args.masterable, args.mr_max), "mastery");
+
+    // Filter relics by the rarity and drop chance of their rewards if requested
+    let filtered_items = stage_counts.track(
+        filter_items_by_reward(filtered_items, args.reward_rarity.as_deref(), args.min_chance),
+        "reward",
+    );
+
+    // Filter and rank items by fuzzy query if provided
+    let filtered_items = stage_counts.track(
+        filter_items_by_fuzzy_query(filtered_items, args.fuzzy.as_deref(), args.min_score),
+        "fuzzy",
+    );
+
+    // Drop excluded items once everything else has been applied
+    stage_counts.track(filter_items_by_exclusions(filtered_items, args.exclusion_queries()), "exclude")
+}
+
+// Exit statuses scripts can branch on; clap already exits with 2 for its own usage errors
+const EXIT_USAGE: i32 = 2;
+const EXIT_PARSE: i32 = 3;
+const EXIT_IO: i32 = 4;
+
+// Why a run stopped short; run picks the message and exit status for each
+#[derive(Debug)]
+enum RunError {
+    Write(io::Error),
+    Interactive(dialoguer::Error),
+    NoRelicNamed(String),
+    NoRelicDrops(String),
+    // Each invalid relic was already reported as it was checked
+    InvalidRewardChances,
+}
+
+impl From<io::Error> for RunError {
+    fn from(err: io::Error) -> RunError {
+        RunError::Write(err)
+    }
+}
+
+fn exit_on_error(result: std::result::Result<(), RunError>) {
+    match result {
+        Ok(()) => {}
+        Err(RunError::Write(err)) => exit_on_write_error(Err(err)),
+        Err(RunError::Interactive(err)) => {
+            eprintln!("error: interactive selection failed: {}", err);
+            process::exit(EXIT_IO);
+        }
+        Err(RunError::NoRelicNamed(relic_name)) => {
+            eprintln!("error: no relic named '{}'", relic_name);
+            process::exit(1);
+        }
+        Err(RunError::NoRelicDrops(term)) => {
+            eprintln!("error: no relic drops an item matching '{}'", term);
+            process::exit(1);
+        }
+        Err(RunError::InvalidRewardChances) => process::exit(1),
+    }
+}
+
+#[derive(Debug)]
+enum InputSource<'a> {
+    Stdin,
+    File(&'a str),
+    Url(&'a str),
+}
+
+impl fmt::Display for InputSource<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputSource::Stdin => write!(f, "stdin"),
+            InputSource::File(path) => write!(f, "input file '{}'", path),
+            InputSource::Url(url) => write!(f, "URL '{}'", url),
+        }
+    }
+}
+
+fn fetch_url(url: &str) -> io::Result<Box<dyn Read>> {
+    match ureq::get(url).call() {
+        Ok(response) if response.status() == 200 => Ok(Box::new(response.into_reader())),
+        Ok(response) | Err(ureq::Error::Status(_, response)) => Err(io::Error::other(format!(
+            "server responded with {} {}",
+            response.status(),
+            response.status_text()
+        ))),
+        Err(err) => Err(io::Error::other(err)),
+    }
+}
+
+#[derive(Deserialize)]
+struct MarketOrdersResponse {
+    payload: MarketOrders,
+}
+
+#[derive(Deserialize)]
+struct MarketOrders {
+    orders: Vec<MarketOrder>,
+}
+
+#[derive(Deserialize)]
+struct MarketOrder {
+    order_type: String,
+    platinum: u32,
+}
+
+// warframe.market asks API clients to stay under about three requests per second
+const MARKET_REQUEST_INTERVAL: Duration = Duration::from_millis(350);
+
+// One entry of the --price-cache file; a null price means the item had no sell orders
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct CachedPrice {
+    price: Option<u32>,
+    fetched_at: DateTime<Utc>,
+}
+
+// Lowest warframe.market sell prices, fetched at most once per urlName during a run
+struct MarketPrices {
+    api_base_url: String,
+    cache: HashMap<String, Option<u32>>,
+    last_request: Option<Instant>,
+    cache_file: Option<String>,
+    cache_ttl: chrono::Duration,
+    stored_prices: BTreeMap<String, CachedPrice>,
+    stored_prices_changed: bool,
+}
+
+impl MarketPrices {
+    fn new(args: &Args) -> MarketPrices {
+        let stored_prices = match &args.price_cache {
+            Some(path) => read_price_cache(path),
+            None => BTreeMap::new(),
+        };
+
+        MarketPrices {
+            api_base_url: args.market_api.trim_end_matches('/').to_string(),
+            cache: HashMap::new(),
+            last_request: None,
+            cache_file: args.price_cache.clone(),
+            cache_ttl: chrono::Duration::minutes(args.price_cache_ttl),
+            stored_prices,
+            stored_prices_changed: false,
+        }
+    }
+
+    // A failed lookup is warned about once and cached as missing, so listing carries on without it.
+    // Only successful lookups are written to the --price-cache file, so failures are retried next run.
+    fn lowest_sell_price(&mut self, url_name: &str) -> Option<u32> {
+        if let Some(price) = self.cache.get(url_name) {
+            return *price;
+        }
+
+        let price = match self.stored_prices.get(url_name) {
+            Some(stored) if Utc::now() - stored.fetched_at < self.cache_ttl => stored.price,
+            _ => match self.fetch_lowest_sell_price(url_name) {
+                Ok(price) => {
+                    let stored = CachedPrice { price, fetched_at: Utc::now() };
+                    self.stored_prices.insert(url_name.to_string(), stored);
+                    self.stored_prices_changed = true;
+                    price
+                }
+                // An expired price is still better than none when the API can't be reached
+                Err(err) => {
+                    log_warning!("warning: failed to fetch the price of {}: {}", url_name, err);
+                    self.stored_prices.get(url_name).and_then(|stored| stored.price)
+                }
+            },
+        };
+        self.cache.insert(url_name.to_string(), price);
+        price
+    }
+
+    // Items without a market entry, or whose price wasn't looked up, have no price
+    fn cached_price(&self, item: &Item) -> Option<u32> {
+        let market = item.warframeMarket.as_ref()?;
+        self.cache.get(&market.urlName).copied().flatten()
+    }
+
+    fn fetch_lowest_sell_price(&mut self, url_name: &str) -> io::Result<Option<u32>> {
+        if let Some(last_request) = self.last_request {
+            thread::sleep(MARKET_REQUEST_INTERVAL.saturating_sub(last_request.elapsed()));
+        }
+        self.last_request = Some(Instant::now());
+
+        let url = format!("{}/items/{}/orders", self.api_base_url, url_name);
+        let response: MarketOrdersResponse = serde_json::from_reader(fetch_url(&url)?)?;
+        Ok(response
+            .payload
+            .orders
+            .iter()
+            .filter(|order| order.order_type == "sell")
+            .map(|order| order.platinum)
+            .min())
+    }
+
+    // Writes newly fetched prices to the --price-cache file. run calls this itself before exiting, since
+    // process::exit would skip a Drop impl.
+    fn save(&mut self) {
+        if let (Some(path), true) = (&self.cache_file, self.stored_prices_changed) {
+            let json = serde_json::to_string_pretty(&self.stored_prices).expect("cached prices always serialize to JSON");
+            if let Err(err) = std::fs::write(path, json) {
+                log_warning!("warning: failed to write price cache '{}': {}", path, err);
+            }
+            self.stored_prices_changed = false;
+        }
+    }
+}
+
+// A missing cache file just means nothing has been cached yet; an unreadable one is warned about and replaced
+fn read_price_cache(path: &str) -> BTreeMap<String, CachedPrice> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return BTreeMap::new(),
+        Err(err) => {
+            log_warning!("warning: failed to read price cache '{}': {}", path, err);
+            return BTreeMap::new();
+        }
+    };
+    serde_json::from_str(&contents).unwrap_or_else(|err| {
+        log_warning!("warning: ignoring malformed price cache '{}': {}", path, err);
+        BTreeMap::new()
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum Compression {
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    const ALL: [Compression; 3] = [Compression::Gzip, Compression::Zstd, Compression::Bzip2];
+
+    fn magic_bytes(&self) -> &'static [u8] {
+        match self {
+            Compression::Gzip => &[0x1f, 0x8b],
+            Compression::Zstd => &[0x28, 0xb5, 0x2f, 0xfd],
+            Compression::Bzip2 => b"BZh",
+        }
+    }
+
+    fn detect(header: &[u8]) -> Option<Compression> {
+        Compression::ALL.into_iter().find(|compression| header.starts_with(compression.magic_bytes()))
+    }
+}
+
+fn open_input(source: &InputSource, compression: Option<Compression>) -> io::Result<Box<dyn Read>> {
+    let input: Box<dyn Read> = match source {
+        InputSource::Stdin => Box::new(io::stdin()),
+        InputSource::File(path) => Box::new(File::open(path)?),
+        InputSource::Url(url) => fetch_url(url)?,
+    };
+
+    // Peek at the first bytes so compressed input is decompressed transparently
+    let mut input = BufReader::new(input);
+    let compression = match compression {
+        Some(compression) => Some(compression),
+        None => Compression::detect(input.fill_buf()?),
+    };
+    match compression {
+        Some(Compression::Gzip) => Ok(Box::new(MultiGzDecoder::new(input))),
+        Some(Compression::Zstd) => Ok(Box::new(zstd::Decoder::with_buffer(input)?)),
+        Some(Compression::Bzip2) => Ok(Box::new(MultiBzDecoder::new(input))),
+        None => Ok(Box::new(input)),
+    }
+}
+
+fn open_input_or_exit(source: &InputSource, compression: Option<Compression>) -> Box<dyn Read> {
+    match open_input(source, compression) {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("error: failed to open {}: {}", source, err);
+            process::exit(EXIT_IO);
+        }
+    }
+}
+
+// Builds a dotted path like "components.masterable" for an ignored field, dropping array indices
+fn unknown_field_path(path: &serde_ignored::Path) -> String {
+    match path {
+        serde_ignored::Path::Root => String::new(),
+        serde_ignored::Path::Seq { parent, .. }
+        | serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => unknown_field_path(parent),
+        serde_ignored::Path::Map { parent, key } => {
+            let parent_path = unknown_field_path(parent);
+            if parent_path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", parent_path, key)
+            }
+        }
+    }
+}
+
+// Parses JSON like `serde_json::from_str`, tallying every field the structs don't model
+fn parse_json_tracking_unknown_fields<T: DeserializeOwned>(json: &str, unknown_fields: &mut BTreeMap<String, usize>) -> Result<T> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let value = serde_ignored::deserialize(&mut deserializer, |path| {
+        *unknown_fields.entry(unknown_field_path(&path)).or_insert(0) += 1;
+    })?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+fn format_unknown_fields(unknown_fields: &BTreeMap<String, usize>) -> String {
+    unknown_fields.keys().cloned().collect::<Vec<String>>().join(", ")
+}
+
+// Files saved by some Windows tools start with a UTF-8 byte order mark, which serde_json rejects
+fn strip_bom(text: &str) -> &str {
+    text.strip_prefix('\u{feff}').unwrap_or(text)
+}
+
+fn read_items_or_exit(
+    source: &InputSource,
+    compression: Option<Compression>,
+    strict: bool,
+    unknown_fields: &mut BTreeMap<String, usize>,
+) -> Vec<Item> {
+    let mut buffer = String::new();
+    if let Err(err) = open_input_or_exit(source, compression).read_to_string(&mut buffer) {
+        eprintln!("error: failed to read {}: {}", source, err);
+        process::exit(EXIT_IO);
+    }
+
+    let json = strip_bom(&buffer);
+
+    // An upstream command that produced nothing is zero items rather than a parse error, unless "--strict" is passed
+    if !strict && json.trim().is_empty() {
+        log_verbose!("info: {} is empty, so it has no items", source);
+        return Vec::new();
+    }
+
+    // A lone top-level object is treated as a single-item list
+    let mut source_unknown_fields = BTreeMap::new();
+    let parsed = if json.trim_start().starts_with('{') {
+        parse_json_tracking_unknown_fields(json, &mut source_unknown_fields).map(|item| vec![item])
+    } else {
+        parse_json_tracking_unknown_fields(json, &mut source_unknown_fields)
+    };
+
+    let items = match parsed {
+        Ok(items) => items,
+        Err(err) => {
+            eprintln!("error: failed to parse input JSON from {}: {}", source, err);
+            process::exit(EXIT_PARSE);
+        }
+    };
+
+    if strict && !source_unknown_fields.is_empty() {
+        eprintln!("error: {} contains unknown fields: {}", source, format_unknown_fields(&source_unknown_fields));
+        process::exit(EXIT_PARSE);
+    }
+    for (field, count) in source_unknown_fields {
+        *unknown_fields.entry(field).or_insert(0) += count;
+    }
+
+    log_verbose!("info: parsed {} item(s) from {}", items.len(), source);
+    items
+}
+
+fn report_unknown_fields(unknown_fields: &BTreeMap<String, usize>) {
+    if unknown_fields.is_empty() {
+        return;
+    }
+
+    eprintln!("note: ignored {} unrecognized field(s):", unknown_fields.len());
+    for (field, count) in unknown_fields {
+        eprintln!("  {} ({} occurrences)", field, count);
+    }
+}
+
+// Appends the items of one input to the merged list, skipping any whose uniqueName already came from an earlier input
+fn merge_items(items: &mut Vec<Item>, seen_unique_names: &mut HashSet<String>, source_items: Vec<Item>) {
+    let new_items: Vec<Item> = source_items.into_iter()
+        .filter(|item| !seen_unique_names.contains(&item.uniqueName))
+        .collect();
+    seen_unique_names.extend(new_items.iter().map(|item| item.uniqueName.clone()));
+    items.extend(new_items);
+}
+
+// Turns each component into an item named after its parent, like "Soma Prime — Barrel", filling in the type and
+// category from the parent where the component has none. Items without components are kept as they are.
+fn flatten_components(mut item: Item) -> Vec<Item> {
+    let Some(components) = item.components.take().filter(|components| !components.is_empty()) else {
+        return vec![item];
+    };
+    components
+        .into_iter()
+        .map(|component| Item {
+            name: format!("{} — {}", item.name, component.name),
+            uniqueName: component.uniqueName,
+            description: component.description,
+            type_: component.type_.unwrap_or_else(|| item.type_.clone()),
+            tradable: component.tradable,
+            category: component.category.or_else(|| item.category.clone()),
+            productCategory: component.productCategory.or_else(|| item.productCategory.clone()),
+            patchlogs: None,
+            components: None,
+            introduced: None,
+            estimatedVaultDate: None,
+            rewards: None,
+            warframeMarket: None,
+            tags: None,
+            masterable: None,
+            masteryReq: None,
+            imageName: None,
+            vaulted: None,
+        })
+        .collect()
+}
+
+// Keeps the first item with each uniqueName, returning how many later duplicates were dropped
+fn dedup_items(items: &mut Vec<Item>) -> usize {
+    let mut seen_unique_names: HashSet<String> = HashSet::new();
+    let item_count = items.len();
+    items.retain(|item| seen_unique_names.insert(item.uniqueName.clone()));
+    item_count - items.len()
+}
+
+fn report_duplicates(dropped_duplicates: usize) {
+    if dropped_duplicates > 0 {
+        log_verbose!("info: dropped {} item(s) with a duplicate uniqueName", dropped_duplicates);
+    }
+}
+
+// Items are split into chunks of this size for --parallel, so threads that finish early can pick up more work
+const PARALLEL_CHUNK_SIZE: usize = 1024;
+
+// Filters chunks on separate threads, keeping the order filter_items would produce
+fn filter_items_in_parallel(items: Vec<Item>, args: &Args, stage_counts: &StageCounts) -> Vec<Item> {
+    let mut chunks: Vec<Vec<Item>> = Vec::new();
+    let mut items = items.into_iter();
+    loop {
+        let chunk: Vec<Item> = items.by_ref().take(PARALLEL_CHUNK_SIZE).collect();
+        if chunk.is_empty() {
+            break;
+        }
+        chunks.push(chunk);
+    }
+
+    // Collecting an indexed parallel iterator keeps the chunks in their original order
+    let filtered_chunks: Vec<(Vec<Item>, StageCounts)> = chunks
+        .into_par_iter()
+        .map(|chunk| {
+            let chunk_counts = StageCounts::default();
+            let filtered: Vec<Item> = filter_items(chunk.into_iter(), args, &chunk_counts).collect();
+            (filtered, chunk_counts)
+        })
+        .collect();
+
+    let mut filtered_items: Vec<Item> = Vec::new();
+    for (filtered, chunk_counts) in filtered_chunks {
+        filtered_items.extend(filtered);
+        stage_counts.merge(chunk_counts);
+    }
+
+    // Each chunk was only ranked against itself, so rank them all together again. The sort is stable and the
+    // chunks are in input order, so equally scored items end up where a single pass would have put them.
+    if let Some(query) = args.fuzzy.as_deref() {
+        let matcher = SkimMatcherV2::default();
+        filtered_items.sort_by_cached_key(|item| Reverse(matcher.fuzzy_match(&item.name, query)));
+    }
+    filtered_items
+}
+
+// Items are filtered in chunks of this size while streaming, so memory stays flat regardless of input size
+const NDJSON_CHUNK_SIZE: usize = 1024;
+
+// Reads one item per line, filtering and logging as it goes so the whole dataset never has to be held in memory.
+// Lines that fail to parse are reported to stderr and skipped. Returns how many items matched, or the first write
+// error, after which nothing more is read.
+fn stream_ndjson_items(
+    sources: &[InputSource],
+    args: &Args,
+    stage_counts: &StageCounts,
+    mut logger: Option<&mut ItemLogger>,
+    unknown_fields: &mut BTreeMap<String, usize>,
+) -> io::Result<usize> {
+    // Returns false once --limit items have been logged, or output can't be written, so reading can stop early
+    let mut remaining = args.limit();
+    let mut matched_count = 0;
+    let mut write_result = Ok(());
+    let mut log_chunk = |chunk: Vec<Item>| {
+        for item in filter_items(chunk.into_iter(), args, stage_counts).take(remaining) {
+            remaining -= 1;
+            matched_count += 1;
+            if let Some(logger) = &mut logger {
+                if let Err(err) = logger.log(&item) {
+                    write_result = Err(err);
+                    return false;
+                }
+            }
+        }
+        remaining > 0
+    };
+
+    // Only track uniqueNames when merging several inputs or deduplicating, so a single large input stays flat in memory
+    let dedup_across_sources = sources.len() > 1;
+    let mut dropped_duplicates = 0;
+    let mut seen_unique_names: HashSet<String> = HashSet::new();
+    let mut chunk: Vec<Item> = Vec::with_capacity(NDJSON_CHUNK_SIZE);
+
+    for source in sources {
+        let input = open_input_or_exit(source, args.compression());
+        let mut source_unique_names: HashSet<String> = HashSet::new();
+        let mut parsed_count = 0;
+
+        for (index, line) in BufReader::new(input).lines().enumerate() {
+            let line_number = index + 1;
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    eprintln!("error: failed to read {} at line {}: {}", source, line_number, err);
+                    process::exit(EXIT_IO);
+                }
+            };
+            let line = if line_number == 1 { strip_bom(&line) } else { &line };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let mut line_unknown_fields = BTreeMap::new();
+            let item: Item = match parse_json_tracking_unknown_fields(line, &mut line_unknown_fields) {
+                Ok(item) => {
+                    parsed_count += 1;
+                    item
+                }
+                Err(err) => {
+                    log_warning!("warning: skipping invalid item in {} on line {}: {}", source, line_number, err);
+                    continue;
+                }
+            };
+            if args.strict && !line_unknown_fields.is_empty() {
+                log_warning!(
+                    "warning: skipping item in {} on line {} with unknown fields: {}",
+                    source,
+                    line_number,
+                    format_unknown_fields(&line_unknown_fields)
+                );
+                continue;
+            }
+            for (field, count) in line_unknown_fields {
+                *unknown_fields.entry(field).or_insert(0) += count;
+            }
+
+            if args.dedup {
+                if !seen_unique_names.insert(item.uniqueName.clone()) {
+                    dropped_duplicates += 1;
+                    continue;
+                }
+            } else if dedup_across_sources {
+                if seen_unique_names.contains(&item.uniqueName) {
+                    continue;
+                }
+                source_unique_names.insert(item.uniqueName.clone());
+            }
+
+            chunk.push(item);
+            if chunk.len() == NDJSON_CHUNK_SIZE
+                && !log_chunk(std::mem::replace(&mut chunk, Vec::with_capacity(NDJSON_CHUNK_SIZE)))
+            {
+                report_duplicates(dropped_duplicates);
+                return write_result.map(|_| matched_count);
+            }
+        }
+
+        log_verbose!("info: parsed {} item(s) from {}", parsed_count, source);
+        seen_unique_names.extend(source_unique_names);
+    }
+
+    log_chunk(chunk);
+    report_duplicates(dropped_duplicates);
+    write_result.map(|_| matched_count)
+}
+
+// --image-base-url's default, which render keeps even when WF_IMAGE_BASE_URL is set
+const DEFAULT_IMAGE_BASE_URL: &str = "https://cdn.warframestat.us/img";
+
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    about = "Filter and display items from the Warframe item export JSON",
+    after_help = "Examples:
+  wf_api_quick -i data.json --search \"Soma Prime\"
+  wf_api_quick -i data.json --names --relic axi
+  cat data.json | wf_api_quick --fmt:csv --columns name,type
+
+Input:
+  Items are read from --input or --url if given, otherwise from the file named by the
+  WF_DATA_FILE environment variable if it is set, otherwise from stdin.
+
+Exit status:
+  1  no relic matched --drops or --drops-from, or --validate found invalid relics
+  2  usage error
+  3  the input could not be parsed
+  4  the input could not be read or the output could not be written"
+)]
+pub struct Args {
+    /// Only keep relics (implies --type Relic), optionally of the given eras (lith, meso, neo, axi),
+    /// comma-separated or repeated
+    #[arg(long, value_name = "TYPES", num_args = 0..=1, value_delimiter = ',', value_parser = RelicType::parse)]
+    relic: Option<Vec<RelicType>>,
+
+    /// The type relics have in the data, used by --relic, relic name searches, --drops and the other relic modes,
+    /// for data that labels relics differently
+    #[arg(long, value_name = "TYPE", default_value = "Relic")]
+    relic_type_label: String,
+
+    /// Only keep relics of the given refinements, comma-separated or repeated. Relics whose names don't end in a
+    /// refinement are kept; --fmt:names lists each relic once either way.
+    #[arg(long, value_name = "STATES", value_enum, value_delimiter = ',', ignore_case = true, requires = "relic")]
+    refinement: Vec<Refinement>,
+
+    /// Only keep items whose type is TYPE, ignoring case (e.g. Warframe, Primary)
+    #[arg(long = "type", value_name = "TYPE")]
+    type_: Option<String>,
+
+    /// Only keep items whose uniqueName has SEGMENT as whole path components, ignoring case (e.g. Projections, Weapons/Tenno)
+    #[arg(long, value_name = "SEGMENT")]
+    path: Option<String>,
+
+    /// Only keep items whose name or uniqueName contains TERM. Prefix TERM with a field, like "type:Warframe",
+    /// to search only that field (name, uniqueName, type, category, productCategory, description).
+    #[arg(long, value_name = "TERM", value_parser = parse_search_term)]
+    search: Option<String>,
+
+    /// Also match --search terms against the aliases of the update that introduced each item (e.g. "Fortuna",
+    /// "Update 24")
+    #[arg(long)]
+    search_aliases: bool,
+
+    /// Match --search anywhere in the name (the default)
+    #[arg(long, conflicts_with_all = ["prefix", "word"])]
+    contains: bool,
+
+    /// Match --search only at the start of the name, as the relic autocomplete workflow expects
+    #[arg(long, conflicts_with = "word")]
+    prefix: bool,
+
+    /// Match --search only as whole words, so "prime" matches "Soma Prime" but not "Primed Chamber"
+    #[arg(long)]
+    word: bool,
+
+    /// Drop items whose name or uniqueName contains TERM (may be repeated)
+    #[arg(long, value_name = "TERM")]
+    exclude: Vec<String>,
+
+    /// Only keep items whose description contains TERM
+    #[arg(long, value_name = "TERM")]
+    search_description: Option<String>,
+
+    /// Compare --search, --search-description and --exclude exactly instead of ignoring case (off by default)
+    #[arg(long)]
+    case_sensitive: bool,
+
+    /// Only keep items whose name matches the regular expression PATTERN
+    #[arg(long, value_name = "PATTERN")]
+    regex: Option<Regex>,
+
+    /// Also match --regex against uniqueName
+    #[arg(long, requires = "regex")]
+    regex_unique_name: bool,
+
+    /// Only keep items whose name fuzzily matches QUERY, best matches first (not supported with --ndjson)
+    #[arg(long, value_name = "QUERY", conflicts_with = "ndjson")]
+    fuzzy: Option<String>,
+
+    /// Minimum score a --fuzzy match needs to be kept
+    #[arg(long, value_name = "SCORE", default_value_t = 0, requires = "fuzzy")]
+    min_score: i64,
+
+    /// Only keep tradable items
+    #[arg(long, conflicts_with = "untradable")]
+    tradable: bool,
+
+    /// Only keep untradable items
+    #[arg(long)]
+    untradable: bool,
+
+    /// Only keep items that have a Warframe.Market entry
+    #[arg(long)]
+    on_market: bool,
+
+    /// Only keep items tagged TAG, ignoring case (may be repeated; items must have every tag)
+    #[arg(long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+
+    /// Only keep items that give mastery
+    #[arg(long)]
+    masterable: bool,
+
+    /// Only keep items whose mastery rank requirement is at most N
+    #[arg(long, value_name = "N")]
+    mr_max: Option<i64>,
+
+    /// Only keep relics with at least one reward of RARITY (Common, Uncommon or Rare), ignoring case
+    #[arg(long, value_name = "RARITY")]
+    reward_rarity: Option<String>,
+
+    /// Only keep relics with a reward dropping at least PCT percent of the time (of --reward-rarity, if given)
+    #[arg(long, value_name = "PCT")]
+    min_chance: Option<f64>,
+
+    /// Only keep items whose category is NAME, ignoring case
+    #[arg(long, value_name = "NAME")]
+    category: Option<String>,
+
+    /// Only keep items whose product category is NAME, ignoring case
+    #[arg(long, value_name = "NAME")]
+    product_category: Option<String>,
+
+    /// Only keep items introduced in the update named NAME, ignoring case (e.g. "Update 15.6")
+    #[arg(long, value_name = "NAME")]
+    introduced: Option<String>,
+
+    /// Only keep items introduced after DATE (YYYY-MM-DD)
+    #[arg(long, value_name = "DATE")]
+    introduced_after: Option<NaiveDate>,
+
+    /// Only keep items introduced before DATE (YYYY-MM-DD)
+    #[arg(long, value_name = "DATE")]
+    introduced_before: Option<NaiveDate>,
+
+    /// Only keep items estimated to be vaulted before DATE (YYYY-MM-DD)
+    #[arg(long, value_name = "DATE")]
+    vaulting_before: Option<NaiveDate>,
+
+    /// Only keep items whose estimated vault date has already passed. Only the date is looked at, so items without
+    /// one are dropped even if the export flags them as vaulted; see --only-vaulted for relics.
+    #[arg(long)]
+    vaulted: bool,
+
+    /// Only keep relics that are vaulted: the export's "vaulted" flag is set or, for items without one, the estimated
+    /// vault date has passed. Unlike --vaulted, which only looks at the date, this trusts the flag first. The flag is
+    /// only as current as the export, so recently vaulted or unvaulted relics may be misjudged.
+    #[arg(long, requires = "relic", conflicts_with = "only_available")]
+    only_vaulted: bool,
+
+    /// Only keep relics still in rotation, the opposite of --only-vaulted. Relics with neither a "vaulted" flag nor
+    /// an estimated vault date count as available.
+    #[arg(long, requires = "relic")]
+    only_available: bool,
+
+    /// Read JSON from PATH instead of stdin (may be repeated to merge files)
+    #[arg(short, long = "input", value_name = "PATH")]
+    inputs: Vec<String>,
+
+    /// Keep only the first item with each uniqueName, even within a single input
+    #[arg(long)]
+    dedup: bool,
+
+    /// Download the JSON from URL instead of reading stdin
+    #[arg(long, value_name = "URL")]
+    url: Option<String>,
+
+    // Read when neither --input nor --url is passed, so the path doesn't have to be typed every time
+    #[arg(skip = env::var("WF_DATA_FILE").ok().filter(|path| !path.is_empty()))]
+    data_file: Option<String>,
+
+    // Terminals that declare themselves dumb can't be relied on to have box-drawing glyphs
+    #[arg(skip = env::var_os("TERM").is_some_and(|term| term == "dumb"))]
+    dumb_terminal: bool,
+
+    /// Treat the input as gzip-compressed even if the magic bytes aren't detected (same as --compression gzip)
+    #[arg(long, conflicts_with = "compression")]
+    gzip: bool,
+
+    /// Decompress the input with FORMAT instead of detecting gzip, zstd or bzip2 from its magic bytes
+    #[arg(long, value_name = "FORMAT")]
+    compression: Option<Compression>,
+
+    /// Read newline-delimited JSON, one item per line, streaming the output
+    #[arg(long)]
+    ndjson: bool,
+
+    /// Filter on every CPU core at once, which helps with large inputs and --regex or --fuzzy (not supported with --ndjson)
+    #[arg(long, conflicts_with = "ndjson")]
+    parallel: bool,
+
+    /// Print each component of the matching items, like "Soma Prime — Barrel", as an item of its own instead of its
+    /// parent (not supported with --ndjson, --batch or --diff)
+    #[arg(long, conflicts_with_all = ["ndjson", "batch", "diff"])]
+    flatten_components: bool,
+
+    /// Write the output to PATH instead of stdout, replacing the file if it exists
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<String>,
+
+    /// Reject input that is empty or contains fields the item model doesn't know about
+    #[arg(long)]
+    strict: bool,
+
+    /// Summarize ignored input fields on stderr
+    #[arg(long)]
+    report_unknown_fields: bool,
+
+    /// Output format (also accepted as "--fmt:<FORMAT>")
+    #[arg(long, value_name = "FORMAT", value_enum, default_value_t = OutputFormat::Default)]
+    fmt: OutputFormat,
+
+    /// Print one name per line, for piping into pickers like fzf (same as "--fmt:names")
+    #[arg(long, visible_alias = "name-only", conflicts_with = "fmt")]
+    names: bool,
+
+    /// Sort items by FIELD; items missing the field go last (not supported with --ndjson)
+    #[arg(long, value_name = "FIELD", value_enum, conflicts_with = "ndjson")]
+    sort: Option<SortField>,
+
+    /// Reverse the --sort order
+    #[arg(long, requires = "sort")]
+    reverse: bool,
+
+    /// Group relics in the names format under "== Lith (42) ==" style era headers (not supported with --ndjson)
+    #[arg(long, requires = "relic", conflicts_with_all = ["ndjson", "batch"])]
+    grouped: bool,
+
+    /// Print each name only once in the names format (relic short names are always deduplicated)
+    #[arg(long)]
+    unique: bool,
+
+    /// When to color field labels and reward rarities in the default format
+    #[arg(long, value_name = "WHEN", value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// Draw the default format's boxes with plain ASCII (the default when TERM is "dumb")
+    #[arg(long, conflicts_with = "no_box")]
+    ascii: bool,
+
+    /// Print the default format without boxes, separating items with a blank line
+    #[arg(long)]
+    no_box: bool,
+
+    /// Box and wrap the default format at N columns instead of the terminal width (0 = no wrapping)
+    #[arg(long, value_name = "N")]
+    width: Option<usize>,
+
+    /// Indent wrapped lines of the default format by N spaces more than the line they continue
+    #[arg(long, value_name = "N", default_value_t = 2)]
+    indent: usize,
+
+    /// Show each item's most recent patchlogs in the default format
+    #[arg(long)]
+    patchlogs: bool,
+
+    /// Number of patchlogs shown per item with --patchlogs
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    patchlogs_limit: usize,
+
+    /// Also show the additions, changes and fixes of each patchlog
+    #[arg(long, requires = "patchlogs")]
+    patchlog_details: bool,
+
+    /// Base URL that --fmt:image joins each item's image name onto
+    #[arg(long, value_name = "URL", env = "WF_IMAGE_BASE_URL", default_value = DEFAULT_IMAGE_BASE_URL)]
+    image_base_url: String,
+
+    /// Show each item's warframe.market page in the default format
+    #[arg(long)]
+    market_url: bool,
+
+    /// Base URL that --market-url and --fmt:market join each item's warframe.market urlName onto
+    #[arg(long, value_name = "URL", default_value = "https://warframe.market/items")]
+    market_base: String,
+
+    /// Look up and show each item's lowest warframe.market sell price in the default format
+    #[arg(long)]
+    prices: bool,
+
+    /// Base URL of the warframe.market API that --prices queries
+    #[arg(long, value_name = "URL", default_value = "https://api.warframe.market/v1")]
+    market_api: String,
+
+    /// Reuse and save looked-up prices in the JSON file at PATH between runs
+    #[arg(long, value_name = "PATH")]
+    price_cache: Option<String>,
+
+    /// How many minutes a --price-cache entry is used before its price is fetched again
+    #[arg(long, value_name = "MINUTES", default_value_t = 60, requires = "price_cache")]
+    price_cache_ttl: i64,
+
+    /// Indent --fmt:json and --fmt:ndjson output for humans instead of writing it compactly
+    #[arg(long)]
+    pretty: bool,
+
+    /// Print at most N items
+    #[arg(long, value_name = "N")]
+    limit: Option<usize>,
+
+    /// Print only the first matching item, after sorting or fuzzy ranking (same as "--limit 1")
+    #[arg(long, visible_alias = "one", conflicts_with = "limit")]
+    first: bool,
+
+    /// Comma-separated sections of the default format to print, in the order given [default: every field; patchlogs, marketUrl and price only with --patchlogs, --market-url and --prices]
+    #[arg(long, value_delimiter = ',', value_parser = BoxField::parse)]
+    fields: Vec<BoxField>,
+
+    /// Comma-separated columns for the CSV and md formats [default: name,uniqueName,type,tradable,category for CSV; name,type,category,tradable for md]
+    #[arg(long, value_delimiter = ',', value_parser = Column::parse)]
+    columns: Vec<Column>,
+
+    /// Print the JSON schema of the items this tool reads, then exit without reading any input
+    #[arg(long)]
+    schema: bool,
+
+    /// Print the inputs, filters, sorting and output format this command would use to stderr, then exit without reading any input
+    #[arg(long)]
+    explain: bool,
+
+    /// Print the reward table of RELIC (e.g. "Lith A1") instead of listing items
+    #[arg(long, value_name = "RELIC", conflicts_with_all = ["ndjson", "count"])]
+    drops: Option<String>,
+
+    /// List the relics that drop an item whose name contains ITEM, most likely first
+    #[arg(long, value_name = "ITEM", conflicts_with_all = ["ndjson", "count", "drops"])]
+    drops_from: Option<String>,
+
+    /// Print how many matching items there are of each type (or category) instead of listing them
+    #[arg(
+        long,
+        value_name = "FIELD",
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "type",
+        conflicts_with_all = ["ndjson", "count", "drops", "drops_from", "batch"]
+    )]
+    stats: Option<StatsField>,
+
+    /// Decimal places to print reward chances with
+    #[arg(long, value_name = "N", default_value_t = 2)]
+    chance_precision: usize,
+
+    /// Print reward chances as rounded "1/9" style odds instead of percentages
+    #[arg(long, conflicts_with = "chance_precision")]
+    chance_as_fraction: bool,
+
+    /// List every reward of the matching relics once, with how many relics drop it and its most common rarity
+    #[arg(long, conflicts_with_all = ["ndjson", "count", "drops", "drops_from", "batch", "stats"])]
+    rewards_summary: bool,
+
+    /// Check that each relic's reward chances add up to 100%, reporting the ones that don't to stderr
+    #[arg(long, conflicts_with_all = ["ndjson", "count", "drops", "drops_from", "batch", "stats", "rewards_summary"])]
+    validate: bool,
+
+    /// How many percentage points a relic's reward chances may be off by for --validate
+    #[arg(long, value_name = "PERCENT", default_value_t = 0.5, requires = "validate")]
+    tolerance: f64,
+
+    /// Report the items added, removed and changed between the exports OLD and NEW, matched by uniqueName
+    /// (filters apply to both)
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["OLD", "NEW"],
+        conflicts_with_all = ["inputs", "url", "ndjson", "batch", "count", "drops", "drops_from", "stats", "rewards_summary", "validate"]
+    )]
+    diff: Option<Vec<String>>,
+
+    /// Parse the input once, then answer each search term read from stdin, one per line (the default and names
+    /// formats head each answer with "== term ==")
+    #[arg(long, conflicts_with_all = ["ndjson", "search", "drops", "drops_from"])]
+    batch: bool,
+
+    /// Print the matching items (the default; kept for existing scripts)
+    #[arg(long, hide = true)]
+    log_items: bool,
+
+    /// Filter without printing the matching items
+    #[arg(short = 'q', long = "quiet", visible_alias = "no-output")]
+    no_output: bool,
+
+    /// Log how many items were parsed and passed each filter, and the width used, to stderr
+    #[arg(short, long, conflicts_with = "silent")]
+    verbose: bool,
+
+    /// Don't print warnings and notes to stderr (errors are still reported)
+    #[arg(short, long)]
+    silent: bool,
+
+    /// Print only the number of matching items instead of listing them
+    #[arg(long)]
+    count: bool,
+
+    /// Pick one matching item from a fuzzy-searchable list of names and print just that one (lists every match when not run in a terminal)
+    #[arg(long, conflicts_with_all = ["ndjson", "batch", "count", "no_output"])]
+    interactive: bool,
+}
+
+impl Args {
+    fn has_relic_arg(&self) -> bool {
+        self.relic.is_some()
+    }
+
+    fn verbosity(&self) -> Verbosity {
+        if self.silent {
+            Verbosity::Silent
+        } else if self.verbose {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        }
+    }
+
+    fn output_format(&self) -> OutputFormat {
+        if self.names {
+            OutputFormat::Search
+        } else {
+            self.fmt
+        }
+    }
+
+    // Coloring automatically is only for output shown in a terminal, not written to a file with --output
+    fn color_enabled(&self) -> bool {
+        match self.color {
+            ColorChoice::Auto if self.output.is_some() => false,
+            color => color.enabled(),
+        }
+    }
+
+    fn vault_state(&self) -> Option<bool> {
+        if self.only_vaulted {
+            Some(true)
+        } else if self.only_available {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    fn compression(&self) -> Option<Compression> {
+        self.compression.or(self.gzip.then_some(Compression::Gzip))
+    }
+
+    fn should_log_items(&self) -> bool {
+        !self.no_output && !self.count
+    }
+
+    fn limit(&self) -> usize {
+        if self.first {
+            1
+        } else {
+            self.limit.unwrap_or(usize::MAX)
+        }
+    }
+
+    fn relic_types(&self) -> Option<&[RelicType]> {
+        self.relic.as_deref()
+    }
+
+    fn search_query(&self) -> Option<SearchQuery> {
+        self.search.as_deref().map(|term| self.search_query_for(term).expect("--search is checked while parsing"))
+    }
+
+    fn search_query_for(&self, term: &str) -> std::result::Result<SearchQuery, String> {
+        let mode = if self.prefix {
+            SearchMode::Prefix
+        } else if self.word {
+            SearchMode::Word
+        } else {
+            SearchMode::Contains
+        };
+        let mut query = SearchQuery::scoped(term, mode, self.case_sensitive)?;
+        query.aliases = self.search_aliases;
+        query.relic_type_label = self.relic_type_label.clone();
+        Ok(query)
+    }
+
+    fn description_query(&self) -> Option<SearchQuery> {
+        self.search_description.as_deref().map(|term| SearchQuery::new(term, SearchMode::Contains, self.case_sensitive))
+    }
+
+    fn drops_from_query(&self) -> Option<SearchQuery> {
+        self.drops_from.as_deref().map(|term| SearchQuery::new(term, SearchMode::Contains, self.case_sensitive))
+    }
+
+    fn exclusion_queries(&self) -> Vec<SearchQuery> {
+        self.exclude
+            .iter()
+            .map(|term| {
+                let mut query = SearchQuery::new(term, SearchMode::Contains, self.case_sensitive);
+                query.relic_type_label = self.relic_type_label.clone();
+                query
+            })
+            .collect()
+    }
+
+    fn tradable(&self) -> Option<bool> {
+        if self.tradable {
+            Some(true)
+        } else if self.untradable {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    fn input_sources(&self) -> Vec<InputSource<'_>> {
+        if !self.inputs.is_empty() {
+            self.inputs.iter().map(|path| InputSource::File(path)).collect()
+        } else if let Some(url) = &self.url {
+            vec![InputSource::Url(url)]
+        } else if let Some(path) = &self.data_file {
+            vec![InputSource::File(path)]
+        } else {
+            vec![InputSource::Stdin]
+        }
+    }
+
+    // Patchlogs, market URLs and prices are left out by default unless "--patchlogs", "--market-url" or "--prices" asks for them
+    fn fields(&self) -> Vec<BoxField> {
+        if self.fields.is_empty() {
+            BoxField::ALL
+                .into_iter()
+                .filter(|field| match field {
+                    BoxField::Patchlogs => self.patchlogs,
+                    BoxField::MarketUrl => self.market_url,
+                    BoxField::Price => self.prices,
+                    _ => true,
+                })
+                .collect()
+        } else {
+            self.fields.clone()
+        }
+    }
+
+    fn chance_format(&self) -> ChanceFormat {
+        ChanceFormat { precision: self.chance_precision, as_fraction: self.chance_as_fraction }
+    }
+
+    fn box_chars(&self) -> Option<&'static BoxChars> {
+        if self.no_box {
+            None
+        } else if self.ascii || self.dumb_terminal {
+            Some(&ASCII_BOX)
+        } else {
+            Some(&UNICODE_BOX)
+        }
+    }
+
+    fn columns(&self) -> Vec<Column> {
+        if self.columns.is_empty() && self.output_format() == OutputFormat::Markdown {
+            Column::MARKDOWN_DEFAULT.to_vec()
+        } else if self.columns.is_empty() {
+            Column::DEFAULT.to_vec()
+        } else {
+            self.columns.clone()
+        }
+    }
+
+    // Lists the pipeline in the order filter_items applies it, leaving out stages that are inactive
+    fn explain(&self) {
+        let mut stages: Vec<(&str, String)> = Vec::new();
+        let sources: Vec<String> = match self.diff.as_deref() {
+            Some(paths) => paths.iter().map(|path| InputSource::File(path).to_string()).collect(),
+            None => self.input_sources().iter().map(|source| source.to_string()).collect(),
+        };
+        let from_env = self.diff.is_none() && self.inputs.is_empty() && self.url.is_none() && self.data_file.is_some();
+        stages.push((
+            "input",
+            format!(
+                "{}{}{}",
+                sources.join(", "),
+                if from_env { " from WF_DATA_FILE" } else { "" },
+                if self.ndjson { " (NDJSON)" } else { "" }
+            ),
+        ));
+
+        match self.relic_types() {
+            Some([]) => stages.push(("relic", String::from("any era"))),
+            Some(relic_types) => {
+                let names: Vec<&str> = relic_types.iter().map(|relic_type| relic_type.name()).collect();
+                stages.push(("relic", names.join(", ")));
+            }
+            None => {}
+        }
+        if self.relic_type_label != "Relic" {
+            stages.push(("relic type label", self.relic_type_label.clone()));
+        }
+        if !self.refinement.is_empty() {
+            let names: Vec<String> = self.refinement.iter().map(|refinement| value_name(*refinement)).collect();
+            stages.push(("refinement", names.join(", ")));
+        }
+        if let Some(type_) = &self.type_ {
+            stages.push(("type", type_.clone()));
+        }
+        if let Some(path) = &self.path {
+            stages.push(("path", path.clone()));
+        }
+        // The term is shown as typed, since the query keeps it lowercased when ignoring case
+        if let (Some(query), Some(term)) = (self.search_query(), &self.search) {
+            let case = if query.case_sensitive { "case-sensitive" } else { "ignoring case" };
+            let mode = format!("{:?}", query.mode).to_lowercase();
+            let description = format!("{:?} ({}, {})", term, mode, case);
+            match query.field {
+                Some(field) => stages.push(("search", format!("{} in {}", description, field.name()))),
+                None if query.aliases => stages.push(("search", format!("{} including update aliases", description))),
+                None => stages.push(("search", description)),
+            }
+        }
+        if let Some(term) = &self.search_description {
+            stages.push(("description", format!("{:?}", term)));
+        }
+        if let Some(regex) = &self.regex {
+            let target = if self.regex_unique_name { "name or uniqueName" } else { "name" };
+            stages.push(("regex", format!("/{}/ against {}", regex, target)));
+        }
+        if let Some(tradable) = self.tradable() {
+            stages.push(("tradable", tradable.to_string()));
+        }
+        if let Some(category) = &self.category {
+            stages.push(("category", category.clone()));
+        }
+        if let Some(product_category) = &self.product_category {
+            stages.push(("product category", product_category.clone()));
+        }
+        if let Some(update_name) = &self.introduced {
+            stages.push(("introduced in", update_name.clone()));
+        }
+        if let Some(date) = self.introduced_after {
+            stages.push(("introduced after", date.to_string()));
+        }
+        if let Some(date) = self.introduced_before {
+            stages.push(("introduced before", date.to_string()));
+        }
+        if let Some(date) = self.vaulting_before {
+            stages.push(("vaulting before", date.to_string()));
+        }
+        if self.vaulted {
+            stages.push(("vaulted", String::from("true")));
+        }
+        match self.vault_state() {
+            Some(true) => stages.push(("vault state", String::from("vaulted"))),
+            Some(false) => stages.push(("vault state", String::from("available"))),
+            None => {}
+        }
+        if self.on_market {
+            stages.push(("on market", String::from("true")));
+        }
+        if !self.tags.is_empty() {
+            stages.push(("tags", self.tags.join(", ")));
+        }
+        if self.masterable {
+            stages.push(("masterable", String::from("true")));
+        }
+        if let Some(mr_max) = self.mr_max {
+            stages.push(("mastery rank at most", mr_max.to_string()));
+        }
+        if let Some(rarity) = &self.reward_rarity {
+            stages.push(("reward rarity", rarity.clone()));
+        }
+        if let Some(min_chance) = self.min_chance {
+            stages.push(("reward chance at least", format!("{}%", min_chance)));
+        }
+        if let Some(query) = &self.fuzzy {
+            stages.push(("fuzzy", format!("{:?} (score at least {})", query, self.min_score)));
+        }
+        if !self.exclude.is_empty() {
+            stages.push(("exclude", self.exclude.join(", ")));
+        }
+        if self.flatten_components {
+            stages.push(("flatten", String::from("components into items of their own")));
+        }
+        if let Some(field) = self.sort {
+            stages.push(("sort", format!("{}{}", value_name(field), if self.reverse { ", reversed" } else { "" })));
+        }
+        if self.limit() != usize::MAX {
+            stages.push(("limit", self.limit().to_string()));
+        }
+        stages.push(("output", self.explain_output()));
+
+        for (stage, value) in stages {
+            eprintln!("{}: {}", stage, value);
+        }
+    }
+
+    // Mirrors the order in which run picks what to print
+    fn explain_output(&self) -> String {
+        if let Some([old_path, new_path]) = self.diff.as_deref() {
+            format!("differences from {:?} to {:?}", old_path, new_path)
+        } else if let Some(relic_name) = &self.drops {
+            format!("drop table of {:?}", relic_name)
+        } else if let Some(item_name) = &self.drops_from {
+            format!("relics dropping {:?}", item_name)
+        } else if let Some(field) = self.stats {
+            format!("counts by {}", value_name(field))
+        } else if self.rewards_summary {
+            String::from("summary of the matching relics' rewards")
+        } else if self.validate {
+            format!("reward chance validation (tolerance {}%)", self.tolerance)
+        } else if self.count {
+            String::from("number of matching items")
+        } else if self.no_output {
+            String::from("nothing")
+        } else if self.batch {
+            format!("{} format, per search term read from stdin", value_name(self.output_format()))
+        } else {
+            format!("{} format", value_name(self.output_format()))
+        }
+    }
+}
+
+// The name a value is spelled with on the command line
+fn value_name(value: impl ValueEnum) -> String {
+    value.to_possible_value().map_or_else(String::new, |value| value.get_name().to_string())
+}
+
+// Prints items in the given format, with every other option at its command-line default.
+// Write errors are returned rather than ending the process, which is left to callers like run.
+pub fn render(items: Vec<Item>, format: OutputFormat) -> io::Result<()> {
+    let args = default_args(format);
+    log_items(items, ItemLogger::new(&args, &mut io::stdout().lock())?)
+}
+
+// Like render, but into any writer, e.g. a Vec<u8> to compare against the expected output.
+// The default format is laid out at 80 columns without color, so the result doesn't depend on the terminal.
+pub fn render_to(writer: &mut impl Write, items: Vec<Item>, format: OutputFormat) -> io::Result<()> {
+    let mut args = default_args(format);
+    args.width = Some(80);
+    args.color = ColorChoice::Never;
+    log_items(items, ItemLogger::new(&args, writer)?)
+}
+
+// Leaves out what the environment would change, so rendering looks the same everywhere: Unicode boxes even when
+// TERM is dumb, the default image base URL whatever WF_IMAGE_BASE_URL says, and no WF_DATA_FILE
+fn default_args(format: OutputFormat) -> Args {
+    let mut args = Args::parse_from([env!("CARGO_PKG_NAME")]);
+    args.fmt = format;
+    args.dumb_terminal = false;
+    args.image_base_url = DEFAULT_IMAGE_BASE_URL.to_string();
+    args.data_file = None;
+    args
+}
+
+// Output that can't be written, like a full disk, ends the run since nothing after it would be seen either.
+// A closed pipe means the reader, like a pager or head, has seen all it wanted, so that isn't an error.
+fn exit_on_write_error<T>(result: io::Result<T>) -> T {
+    result.unwrap_or_else(|err| {
+        if err.kind() == io::ErrorKind::BrokenPipe {
+            process::exit(0);
+        }
+        eprintln!("error: failed to write output: {}", err);
+        process::exit(EXIT_IO);
+    })
+}
+
+// Everything after argument parsing, so the binary stays a thin wrapper around the library
+pub fn run(args: Args) {
+    VERBOSITY.store(args.verbosity() as u8, atomic::Ordering::Relaxed);
+
+    // Describe what would be done without reading any input, or creating the --output file, if "--explain" is passed
+    if args.explain {
+        args.explain();
+        return;
+    }
+
+    let mut out = open_output_or_exit(args.output.as_deref());
+
+    // Print the schema of the item model without reading any input if "--schema" is passed
+    if args.schema {
+        let schema = serde_json::to_string_pretty(&schema_for!(Item)).expect("schemas always serialize to JSON");
+        exit_on_write_error(writeln!(out, "{}", schema).and_then(|_| out.flush()));
+        return;
+    }
+
+    let input_sources = args.input_sources();
+    let mut unknown_fields: BTreeMap<String, usize> = BTreeMap::new();
+    let stage_counts = StageCounts::default();
+
+    // Compare two exports, filtering both the same way, instead of listing items if "--diff" is passed
+    if let Some([old_path, new_path]) = args.diff.as_deref() {
+        let [old_items, new_items] = [old_path, new_path].map(|path| {
+            let items = read_items_or_exit(&InputSource::File(path), args.compression(), args.strict, &mut unknown_fields);
+            filter_items(items.into_iter(), &args, &stage_counts).collect::<Vec<Item>>()
+        });
+        if args.report_unknown_fields {
+            report_unknown_fields(&unknown_fields);
+        }
+        exit_on_write_error(log_diff(&mut out, &old_items, &new_items).and_then(|_| out.flush()));
+        return;
+    }
+
+    if args.batch && input_sources.iter().any(|source| matches!(source, InputSource::Stdin)) {
+        eprintln!("error: --batch reads search terms from stdin, so items must come from --input or --url");
+        process::exit(EXIT_USAGE);
+    }
+
+    // One set of prices for the whole run, so the --price-cache file is read once and saved once
+    let mut prices = MarketPrices::new(&args);
+
+    // Items are streamed one per line in NDJSON mode
+    if args.ndjson {
+        if !args.should_log_items() {
+            let matched_count =
+                exit_on_write_error(stream_ndjson_items(&input_sources, &args, &stage_counts, None, &mut unknown_fields));
+            stage_counts.report();
+            if args.count {
+                exit_on_write_error(writeln!(out, "{}", matched_count).and_then(|_| out.flush()));
+            }
+        } else {
+            let mut logger = exit_on_write_error(ItemLogger::new(&args, &mut out)).with_prices(&mut prices);
+            let result = stream_ndjson_items(&input_sources, &args, &stage_counts, Some(&mut logger), &mut unknown_fields)
+                .and_then(|matched_count| logger.finish().map(|_| matched_count));
+            prices.save();
+            let matched_count = exit_on_write_error(result);
+            stage_counts.report();
+            note_if_nothing_matched(matched_count, &args);
+        }
+        if args.report_unknown_fields {
+            report_unknown_fields(&unknown_fields);
+        }
+        return;
+    }
+
+    // Read JSON data from each input file or URL, falling back to stdin
+    let mut items: Vec<Item> = Vec::new();
+    let mut seen_unique_names: HashSet<String> = HashSet::new();
+    for source in &input_sources {
+        let source_items = read_items_or_exit(source, args.compression(), args.strict, &mut unknown_fields);
+        if input_sources.len() > 1 {
+            merge_items(&mut items, &mut seen_unique_names, source_items);
+        } else {
+            items = source_items;
+        }
+    }
+
+    // Drop repeated uniqueNames, including ones within a single input, if "--dedup" is passed
+    if args.dedup {
+        report_duplicates(dedup_items(&mut items));
+    }
+
+    if args.report_unknown_fields {
+        report_unknown_fields(&unknown_fields);
+    }
+
+    let filtered_items: Box<dyn Iterator<Item = Item>> = if args.parallel {
+        Box::new(filter_items_in_parallel(items, &args, &stage_counts).into_iter())
+    } else {
+        Box::new(filter_items(items.into_iter(), &args, &stage_counts))
+    };
+
+    // Replace each matching item with its components if "--flatten-components" is passed, so sorting, --limit and
+    // every output format see the components as items of their own
+    let filtered_items: Box<dyn Iterator<Item = Item>> = if args.flatten_components {
+        Box::new(filtered_items.flat_map(flatten_components))
+    } else {
+        filtered_items
+    };
+
+    // Answer repeated searches against the filtered items, which only had to be parsed once
+    if args.batch {
+        let mut items: Vec<Item> = filtered_items.collect();
+        stage_counts.report();
+        if let Some(field) = args.sort {
+            sort_items(&mut items, field, args.reverse, &mut prices);
+        }
+        let result = answer_queries(&mut out, &Index::new(items), &args, &mut prices);
+        prices.save();
+        exit_on_write_error(result);
+        return;
+    }
+
+    let limit = args.limit();
+
+    // Sort the results if "--sort" is passed, then truncate them if "--limit" is passed
+    let filtered_items: Vec<Item> = match args.sort {
+        Some(field) => {
+            let mut sorted_items: Vec<Item> = filtered_items.collect();
+            sort_items(&mut sorted_items, field, args.reverse, &mut prices);
+            sorted_items.truncate(limit);
+            sorted_items
+        }
+        // Without sorting the pipeline can stop as soon as enough items have matched
+        None => filtered_items.take(limit).collect(),
+    };
+    stage_counts.report();
+    note_if_nothing_matched(filtered_items.len(), &args);
+
+    let result = log_matches(&mut out, filtered_items, &args, &mut prices).and_then(|_| Ok(out.flush()?));
+    prices.save();
+    exit_on_error(result);
+}
+
+// Writes whichever report the arguments ask for, or else the matching items themselves
+fn log_matches(
+    out: &mut dyn Write,
+    items: Vec<Item>,
+    args: &Args,
+    prices: &mut MarketPrices,
+) -> std::result::Result<(), RunError> {
+
+    if let Some(relic_name) = &args.drops {
+        log_drop_tables(out, &items, relic_name, &args.relic_type_label, args.chance_format())
+    } else if let Some(query) = args.drops_from_query() {
+        log_reward_sources(out, &items, &query, &args.relic_type_label, args.chance_format())
+    } else if let Some(field) = args.stats {
+        Ok(log_stats(out, &items, field)?)
+    } else if args.rewards_summary {
+        Ok(log_rewards_summary(out, &items, &args.relic_type_label)?)
+    } else if args.validate {
+        validate_reward_chances(&items, args.tolerance, &args.relic_type_label)
+    } else if args.count {
+        Ok(writeln!(out, "{}", items.len())?)
+    } else if args.interactive && io::stdin().is_terminal() && io::stdout().is_terminal() {
+        select_and_log_item(items, ItemLogger::new(args, out)?.with_prices(prices))
+    } else if args.should_log_items() {
+        Ok(log_items(items, ItemLogger::new(args, out)?.with_prices(prices))?)
+    } else {
+        Ok(())
+    }
+}
+
+// An empty listing looks just like a crash, so say why stdout is empty on stderr, where pipelines won't see it.
+// --drops and --drops-from already fail when nothing matches, and --validate only reports problems.
+fn note_if_nothing_matched(matched_count: usize, args: &Args) {
+    let reports_own_misses = args.drops.is_some() || args.drops_from.is_some() || args.validate;
+    if matched_count == 0 && args.should_log_items() && !reports_own_misses {
+        log_warning!("note: no items matched your filters");
+    }
+}
+
+// Buffers output going to a file, since it isn't being watched line by line like a terminal
+fn open_output_or_exit(path: Option<&str>) -> Box<dyn Write> {
+    let Some(path) = path else {
+        return Box::new(io::stdout().lock());
+    };
+    match File::create(path) {
+        Ok(file) => Box::new(io::BufWriter::new(file)),
+        Err(err) => {
+            eprintln!("error: failed to create output file '{}': {}", path, err);
+            process::exit(EXIT_IO);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    // Just the fields every item has; tests set the rest themselves
+    fn item(name: &str, unique_name: &str, type_: &str) -> Item {
+        let json = serde_json::json!({ "name": name, "uniqueName": unique_name, "type": type_, "tradable": true });
+        serde_json::from_value(json).expect("test items are valid")
+    }
+
+    // A relic dropping each (rarity, chance, item name) reward
+    fn relic(name: &str, unique_name: &str, rewards: &[(&str, f64, &str)]) -> Item {
+        let mut relic = item(name, unique_name, "Relic");
+        let rewards = rewards
+            .iter()
+            .map(|(rarity, chance, item_name)| {
+                let reward_item = serde_json::json!({ "name": item_name, "uniqueName": format!("/Lotus/Test/{}", item_name) });
+                Reward {
+                    rarity: rarity.to_string(),
+                    chance: *chance,
+                    item: serde_json::from_value(reward_item).expect("test rewards are valid"),
+                }
+            })
+            .collect();
+        relic.rewards = Some(rewards);
+        relic
+    }
+
+    fn render_string(items: Vec<Item>, format: OutputFormat) -> String {
+        let mut out = Vec::new();
+        render_to(&mut out, items, format).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn search(items: Vec<Item>, term: &str, mode: SearchMode) -> Vec<String> {
+        let query = SearchQuery::new(term, mode, false);
+        filter_items_by_search_term(items.into_iter(), Some(query)).map(|item| item.name).collect()
+    }
+
+    fn names(items: &[Item]) -> Vec<&str> {
+        items.iter().map(|item| item.name.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_pretty_printed_json_read_from_a_reader() {
+        let json = r#"[
+  {
+    "name": "Soma Prime",
+    "uniqueName": "/Lotus/Weapons/Tenno/Rifle/PrimeSoma",
+    "type": "Primary",
+    "tradable": false
+  },
+  {
+    "name": "Lith A1 Intact",
+    "uniqueName": "/Lotus/Types/Game/Projections/T1VoidProjectionRevenantPrimeABronze",
+    "type": "Relic",
+    "tradable": true
+  }
+]
+"#;
+        let mut buffer = String::new();
+        Cursor::new(json).read_to_string(&mut buffer).unwrap();
+        let items: Vec<Item> = serde_json::from_str(&buffer).unwrap();
+        assert_eq!(names(&items), ["Soma Prime", "Lith A1 Intact"]);
+        assert_eq!(items[1].type_, "Relic");
+    }
+
+    const SMALL_EXPORT: &str = r#"[{"name":"Soma Prime","uniqueName":"/Lotus/Weapons/Tenno/Rifle/PrimeSoma","type":"Primary","tradable":false},{"name":"Lith A1 Intact","uniqueName":"/Lotus/Types/Game/Projections/T1VoidProjectionRevenantPrimeABronze","type":"Relic","tradable":true}]"#;
+
+    // Writes the bytes to a file of their own, so open_input sees them the way it sees "--input PATH"
+    fn read_items_from_file(file_name: &str, bytes: &[u8], compression: Option<Compression>) -> Vec<Item> {
+        let path = env::temp_dir().join(format!("wf_api_quick_{}_{}", process::id(), file_name));
+        std::fs::write(&path, bytes).unwrap();
+        let mut buffer = String::new();
+        open_input(&InputSource::File(path.to_str().unwrap()), compression).unwrap().read_to_string(&mut buffer).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        parse_json_tracking_unknown_fields(&buffer, &mut BTreeMap::new()).unwrap()
+    }
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn gzip_input_is_detected_from_its_magic_bytes() {
+        let items = read_items_from_file("detected.json.gz", &gzip(SMALL_EXPORT.as_bytes()), None);
+        assert_eq!(names(&items), ["Soma Prime", "Lith A1 Intact"]);
+    }
+
+    #[test]
+    fn gzip_flag_decompresses_input() {
+        let items = read_items_from_file("forced.json.gz", &gzip(SMALL_EXPORT.as_bytes()), Some(Compression::Gzip));
+        assert_eq!(names(&items), ["Soma Prime", "Lith A1 Intact"]);
+    }
+
+    #[test]
+    fn uncompressed_input_is_read_as_is() {
+        let items = read_items_from_file("plain.json", SMALL_EXPORT.as_bytes(), None);
+        assert_eq!(names(&items), ["Soma Prime", "Lith A1 Intact"]);
+    }
+
+    fn zstd(bytes: &[u8]) -> Vec<u8> {
+        zstd::encode_all(bytes, 0).unwrap()
+    }
+
+    fn bzip2(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn zstd_input_is_detected_from_its_magic_bytes() {
+        let items = read_items_from_file("detected.json.zst", &zstd(SMALL_EXPORT.as_bytes()), None);
+        assert_eq!(names(&items), ["Soma Prime", "Lith A1 Intact"]);
+    }
+
+    #[test]
+    fn zstd_flag_decompresses_input() {
+        let items = read_items_from_file("forced.json.zst", &zstd(SMALL_EXPORT.as_bytes()), Some(Compression::Zstd));
+        assert_eq!(names(&items), ["Soma Prime", "Lith A1 Intact"]);
+    }
+
+    #[test]
+    fn bzip2_input_is_detected_from_its_magic_bytes() {
+        let items = read_items_from_file("detected.json.bz2", &bzip2(SMALL_EXPORT.as_bytes()), None);
+        assert_eq!(names(&items), ["Soma Prime", "Lith A1 Intact"]);
+    }
+
+    #[test]
+    fn bzip2_flag_decompresses_input() {
+        let items = read_items_from_file("forced.json.bz2", &bzip2(SMALL_EXPORT.as_bytes()), Some(Compression::Bzip2));
+        assert_eq!(names(&items), ["Soma Prime", "Lith A1 Intact"]);
+    }
+
+    #[test]
+    fn csv_quotes_values_containing_commas() {
+        let mut item = item("Gara, Prime", "/Lotus/Powersuits/Glass/GaraPrime", "Warframe");
+        item.category = Some(String::from("Warframes"));
+        let csv = render_string(vec![item], OutputFormat::Csv);
+        assert_eq!(
+            csv,
+            "name,uniqueName,type,tradable,category\n\"Gara, Prime\",/Lotus/Powersuits/Glass/GaraPrime,Warframe,true,Warframes\n"
+        );
+    }
+
+    fn soma_items() -> Vec<Item> {
+        vec![
+            item("Soma Prime", "/Lotus/Weapons/Tenno/Rifle/PrimeSoma", "Primary"),
+            item("Soma", "/Lotus/Weapons/Tenno/Rifle/Soma", "Primary"),
+            item("Primed Chamber", "/Lotus/Upgrades/Mods/Sniper/PrimedChamber", "Mod"),
+        ]
+    }
+
+    #[test]
+    fn contains_search_matches_anywhere_in_the_name() {
+        assert_eq!(search(soma_items(), "prime", SearchMode::Contains), ["Soma Prime", "Primed Chamber"]);
+        assert_eq!(search(soma_items(), "SOMA", SearchMode::Contains), ["Soma Prime", "Soma"]);
+    }
+
+    #[test]
+    fn prefix_search_matches_only_the_start_of_the_name() {
+        assert_eq!(search(soma_items(), "prime", SearchMode::Prefix), ["Primed Chamber"]);
+        assert_eq!(search(soma_items(), "soma p", SearchMode::Prefix), ["Soma Prime"]);
+    }
+
+    fn categorized(name: &str, category: Option<&str>, product_category: Option<&str>) -> Item {
+        let mut item = item(name, &format!("/Lotus/Test/{}", name), "Warframe");
+        item.category = category.map(String::from);
+        item.productCategory = product_category.map(String::from);
+        item
+    }
+
+    #[test]
+    fn category_filter_ignores_case_and_drops_items_without_a_category() {
+        let items = vec![
+            categorized("Volt", Some("Warframes"), Some("Suits")),
+            categorized("Soma", Some("Primary"), None),
+            categorized("Mystery", None, None),
+        ];
+        let matched: Vec<Item> = filter_items_by_category(items.into_iter(), Some("warframes")).collect();
+        assert_eq!(names(&matched), ["Volt"]);
+    }
+
+    #[test]
+    fn product_category_filter_drops_items_without_a_product_category() {
+        let items = vec![categorized("Volt", Some("Warframes"), Some("Suits")), categorized("Soma", Some("Primary"), None)];
+        let matched: Vec<Item> = filter_items_by_product_category(items.into_iter(), Some("Suits")).collect();
+        assert_eq!(names(&matched), ["Volt"]);
+    }
+
+    #[test]
+    fn category_filter_keeps_everything_when_inactive() {
+        let items = vec![categorized("Volt", Some("Warframes"), None), categorized("Mystery", None, None)];
+        let matched: Vec<Item> = filter_items_by_category(items.into_iter(), None).collect();
+        assert_eq!(names(&matched), ["Volt", "Mystery"]);
+    }
+
+    fn vaulting(name: &str, vault_date: Option<NaiveDate>) -> Item {
+        let mut item = item(name, &format!("/Lotus/Test/{}", name), "Relic");
+        item.estimatedVaultDate = vault_date.map(|date| date.format("%Y-%m-%d").to_string());
+        item
+    }
+
+    #[test]
+    fn vaulted_keeps_items_whose_vault_date_was_yesterday() {
+        let today = Local::now().date_naive();
+        let items = vec![
+            vaulting("Yesterday", today.pred_opt()),
+            vaulting("Today", Some(today)),
+            vaulting("Tomorrow", today.succ_opt()),
+            vaulting("Undated", None),
+        ];
+        let matched: Vec<Item> = filter_items_by_vault_date(items.into_iter(), None, true, today).collect();
+        assert_eq!(names(&matched), ["Yesterday"]);
+    }
+
+    #[test]
+    fn vaulting_before_drops_items_without_a_vault_date() {
+        let today = Local::now().date_naive();
+        let items = vec![vaulting("Tomorrow", today.succ_opt()), vaulting("Undated", None)];
+        let before = today.checked_add_days(chrono::Days::new(7));
+        let matched: Vec<Item> = filter_items_by_vault_date(items.into_iter(), before, false, today).collect();
+        assert_eq!(names(&matched), ["Tomorrow"]);
+    }
+
+    #[test]
+    fn border_width_never_underflows_on_tiny_terminals() {
+        assert_eq!(border_width(0), MIN_BORDER_WIDTH);
+        assert_eq!(border_width(1), MIN_BORDER_WIDTH);
+        assert_eq!(border_width(80), 78);
+    }
+
+    #[test]
+    fn wrapped_lines_fit_the_width() {
+        let description = "An artifact containing Orokin secrets. It can only be opened through the power of the Void, \
+                           which rewards one of six items at random when it is cracked.";
+        let lines = wrap_text(description, "Description:", 40, 2);
+        assert!(lines.len() > 1);
+        for line in &lines {
+            let width = unicode_width::UnicodeWidthStr::width(line.as_str());
+            assert!(width <= 40, "{:?} is {} columns wide", line, width);
+        }
+        assert_eq!(lines.join(" ").split_whitespace().collect::<Vec<&str>>().join(" "), format!("Description: {}", description));
+    }
+
+    #[test]
+    fn padded_box_lines_end_at_the_right_border() {
+        for width in [1, 40, 80] {
+            let args = Args::parse_from([env!("CARGO_PKG_NAME"), "--width", &width.to_string()]);
+            let mut out = io::sink();
+            let logger = ItemLogger::new(&args, &mut out).unwrap();
+
+            // "│ " + the text padded to wrap_width + " │" has to be as wide as "┌" + the rule + "┐"
+            assert_eq!(2 + logger.wrap_width() + 2, 1 + border_width(width) + 1, "--width {}", width);
+        }
+    }
+
+    #[test]
+    fn box_lines_end_at_the_border_even_when_a_word_is_too_wide() {
+        let relic = item("Axi A1 Exceptional", "/Lotus/Types/Game/Projections/T4VoidProjectionESilverWithAVeryLongSuffix", "Relic");
+        let mut args = default_args(OutputFormat::Default);
+        args.width = Some(40);
+        args.color = ColorChoice::Never;
+        let mut out = Vec::new();
+        log_items(vec![relic], ItemLogger::new(&args, &mut out).unwrap()).unwrap();
+        let output = String::from_utf8(out).unwrap();
+        for line in output.lines() {
+            assert_eq!(display_width(line), 40, "{:?}", line);
+        }
+    }
+
+    fn drops_from(items: &[Item], term: &str) -> Vec<String> {
+        let query = SearchQuery::new(term, SearchMode::Contains, false);
+        reward_sources(items, &query, "Relic")
+            .into_iter()
+            .map(|(short_name, reward)| format!("{} {}", short_name, ChanceFormat::default().describe(reward)))
+            .collect()
+    }
+
+    #[test]
+    fn drops_from_lists_every_relic_dropping_the_part_most_likely_first() {
+        let items = vec![
+            relic(
+                "Lith A1 Intact",
+                "/Lotus/Types/Game/Projections/T1VoidProjectionAshPrimeABronze",
+                &[("Rare", 2.0, "Ash Prime Systems Blueprint"), ("Common", 25.33, "Forma Blueprint")],
+            ),
+            relic("Meso B2 Intact", "/Lotus/Types/Game/Projections/T2VoidProjectionAshPrimeBBronze", &[("Uncommon", 11.0, "Ash Prime Systems Blueprint")]),
+            relic("Neo C3 Intact", "/Lotus/Types/Game/Projections/T3VoidProjectionVoltPrimeCBronze", &[("Rare", 2.0, "Volt Prime Neuroptics Blueprint")]),
+        ];
+        assert_eq!(
+            drops_from(&items, "ash prime systems"),
+            ["Meso B2 [Uncommon 11.00%] Ash Prime Systems Blueprint", "Lith A1 [Rare 2.00%] Ash Prime Systems Blueprint"]
+        );
+    }
+
+    #[test]
+    fn drops_from_lists_each_relic_once_across_refinements() {
+        let unique_name = "/Lotus/Types/Game/Projections/T1VoidProjectionAshPrimeABronze";
+        let items = vec![
+            relic("Lith A1 Intact", unique_name, &[("Rare", 2.0, "Ash Prime Systems Blueprint")]),
+            relic("Lith A1 Radiant", unique_name, &[("Rare", 10.0, "Ash Prime Systems Blueprint")]),
+        ];
+        assert_eq!(drops_from(&items, "ash prime systems"), ["Lith A1 [Rare 2.00%] Ash Prime Systems Blueprint"]);
+    }
+
+    #[test]
+    fn rewards_summary_names_the_most_common_rarity() {
+        let items = vec![
+            relic("Lith A1 Intact", "/Lotus/Types/Game/Projections/T1VoidProjectionA1Bronze", &[("Rare", 2.0, "Forma Blueprint")]),
+            relic("Meso B2 Intact", "/Lotus/Types/Game/Projections/T2VoidProjectionB2Bronze", &[("Uncommon", 11.0, "Forma Blueprint")]),
+        ];
+        let mut out = Vec::new();
+        log_rewards_summary(&mut out, &items, "Relic").unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "Forma Blueprint: 2 relic(s), mostly Uncommon\n");
+    }
+
+    // Reports that find nothing hand back an error for run to turn into an exit status, instead of exiting themselves
+    #[test]
+    fn reports_that_find_nothing_return_errors() {
+        let unique_name = "/Lotus/Types/Game/Projections/T1VoidProjectionAshPrimeABronze";
+        let items = vec![relic("Lith A1 Intact", unique_name, &[("Rare", 2.0, "Ash Prime Systems Blueprint")])];
+        let mut out = Vec::new();
+
+        let err = log_drop_tables(&mut out, &items, "Axi Z9", "Relic", ChanceFormat::default()).unwrap_err();
+        assert!(matches!(err, RunError::NoRelicNamed(name) if name == "Axi Z9"));
+        let query = SearchQuery::new("forma", SearchMode::Contains, false);
+        let err = log_reward_sources(&mut out, &items, &query, "Relic", ChanceFormat::default()).unwrap_err();
+        assert!(matches!(err, RunError::NoRelicDrops(term) if term == "forma"));
+        let err = validate_reward_chances(&items, 0.01, "Relic").unwrap_err();
+        assert!(matches!(err, RunError::InvalidRewardChances));
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn filter_pipeline_pulls_only_as_many_items_as_it_needs() {
+        let args = Args::parse_from([env!("CARGO_PKG_NAME"), "--type", "primary", "--search", "soma"]);
+        let pulled = std::cell::Cell::new(0);
+        // Endless input, which a pipeline collecting each stage into a Vec would never get through
+        let items = (0..)
+            .map(|index| {
+                if index % 3 == 0 {
+                    item(&format!("Soma {}", index), &format!("/Lotus/Weapons/Tenno/Rifle/Soma{}", index), "Primary")
+                } else {
+                    item(&format!("Mod {}", index), &format!("/Lotus/Upgrades/Mods/Mod{}", index), "Mod")
+                }
+            })
+            .inspect(|_| pulled.set(pulled.get() + 1));
+        let first_two: Vec<Item> = filter_items(items, &args, &StageCounts::default()).take(2).collect();
+        assert_eq!(names(&first_two), ["Soma 0", "Soma 3"]);
+        assert_eq!(pulled.get(), 4);
+    }
+
+    #[test]
+    fn filter_pipeline_matches_filtering_each_stage_into_a_vec() {
+        let args = Args::parse_from([env!("CARGO_PKG_NAME"), "--type", "primary", "--search", "soma", "--exclude", "soma prime"]);
+        let staged: Vec<Item> = filter_items_by_type(soma_items().into_iter(), args.type_.as_deref()).collect();
+        let staged: Vec<Item> = filter_items_by_search_term(staged.into_iter(), args.search_query()).collect();
+        let staged: Vec<Item> = filter_items_by_exclusions(staged.into_iter(), args.exclusion_queries()).collect();
+        let piped: Vec<Item> = filter_items(soma_items().into_iter(), &args, &StageCounts::default()).collect();
+        assert_eq!(names(&piped), names(&staged));
+        assert_eq!(names(&piped), ["Soma"]);
+    }
+
+    #[test]
+    fn exported_filters_compose_like_the_cli_pipeline() {
+        let items = vec![
+            item("Lith A1 Intact", "/Lotus/Types/Game/Projections/T1VoidProjectionA1Bronze", "Relic"),
+            item("Axi A1 Intact", "/Lotus/Types/Game/Projections/T4VoidProjectionA1Bronze", "Relic"),
+            item("Lith Prime", "/Lotus/Weapons/Tenno/LithPrime", "Primary"),
+        ];
+        let relics = filter_items_by_relic_type(items.into_iter(), Some(&[]), "Relic");
+        let query = SearchQuery::new("lith", SearchMode::Prefix, false);
+        let matched: Vec<Item> = filter_items_by_search_term(relics, Some(query)).collect();
+        assert_eq!(names(&matched), ["Lith A1 Intact"]);
+    }
+
+    #[test]
+    fn relic_types_parse_case_insensitively() {
+        assert_eq!(RelicType::from_str("lith"), Some(RelicType::Lith));
+        assert_eq!(RelicType::from_str("meso"), Some(RelicType::Meso));
+        assert_eq!(RelicType::from_str("neo"), Some(RelicType::Neo));
+        assert_eq!(RelicType::from_str("axi"), Some(RelicType::Axi));
+        assert_eq!(RelicType::from_str("AXI"), Some(RelicType::Axi));
+        assert_eq!(RelicType::from_str("LiTh"), Some(RelicType::Lith));
+        assert_eq!(RelicType::from_str("tauforged"), None);
+        assert!(RelicType::parse("tauforged").is_err());
+    }
+
+    #[test]
+    fn relics_match_their_era_whatever_the_case_of_the_name() {
+        let relic = item("AXI S12 Relic", "/Lotus/Types/Game/Projections/T4VoidProjectionS12Bronze", "Relic");
+        assert_eq!(relic.relic_type(), Some(RelicType::Axi));
+        assert!(item_is_relic_of_type(&relic, &[RelicType::Axi], "Relic"));
+        assert!(item_is_relic_of_type(&relic, &[RelicType::Lith, RelicType::Axi], "Relic"));
+        assert!(item_is_relic_of_type(&relic, &[], "Relic"));
+        assert!(!item_is_relic_of_type(&relic, &[RelicType::Lith], "Relic"));
+
+        let not_a_relic = item("Axi Prime", "/Lotus/Weapons/Tenno/AxiPrime", "Rifle");
+        assert!(!item_is_relic_of_type(&not_a_relic, &[RelicType::Axi], "Relic"));
+    }
+
+    #[test]
+    fn relics_with_unusual_names_fall_back_to_the_void_tier() {
+        let renamed = item("Soma Prime Relic A", "/Lotus/Types/Game/Projections/T1VoidProjectionSomaABronze", "Relic");
+        assert_eq!(renamed.relic_type(), Some(RelicType::Lith));
+        assert!(item_is_relic_of_type(&renamed, &[RelicType::Lith], "Relic"));
+        assert!(!item_is_relic_of_type(&renamed, &[RelicType::Axi], "Relic"));
+
+        // The path's "/Lotus/Types" prefix doesn't look like any era
+        let unknown = item("Mystery Relic", "/Lotus/Types/Game/Projections/MysteryProjection", "Relic");
+        assert_eq!(unknown.relic_type(), None);
+        assert!(!item_is_relic_of_type(&unknown, &[RelicType::Lith], "Relic"));
+    }
+
+    #[test]
+    fn void_tiers_map_to_eras() {
+        let projection = |tier: &str| format!("/Lotus/Types/Game/Projections/{}VoidProjectionVoltPrimeBronze", tier);
+        assert_eq!(RelicType::from_unique_name(&projection("T1")), Some(RelicType::Lith));
+        assert_eq!(RelicType::from_unique_name(&projection("T2")), Some(RelicType::Meso));
+        assert_eq!(RelicType::from_unique_name(&projection("T3")), Some(RelicType::Neo));
+        assert_eq!(RelicType::from_unique_name(&projection("T4")), Some(RelicType::Axi));
+        assert_eq!(RelicType::from_unique_name(&projection("T5")), None);
+        assert_eq!(RelicType::from_unique_name("/Lotus/Weapons/Tenno/Rifle/SomaPrime"), None);
+        assert_eq!(RelicType::from_unique_name("VoidProjection"), None);
+    }
+
+    #[test]
+    fn mr_max_keeps_items_at_exactly_that_rank() {
+        let with_mastery = |name: &str, mastery_req: Option<i64>| {
+            let mut item = item(name, &format!("/Lotus/Weapons/Tenno/{}", name), "Rifle");
+            item.masteryReq = mastery_req;
+            item
+        };
+        let items = vec![with_mastery("AtMax", Some(8)), with_mastery("AboveMax", Some(9)), with_mastery("Unranked", None)];
+        let kept: Vec<Item> = filter_items_by_mastery(items.into_iter(), false, Some(8)).collect();
+        assert_eq!(names(&kept), ["AtMax"]);
+    }
+
+    #[test]
+    fn word_search_matches_whole_words_only() {
+        let items = vec![
+            item("Soma Prime", "/Lotus/Weapons/Tenno/Rifle/SomaPrime", "Rifle"),
+            item("Prime Blueprint", "/Lotus/Types/Recipes/PrimeBlueprint", "Misc"),
+            item("Primed Chamber", "/Lotus/Upgrades/Mods/Rifle/PrimedChamber", "Mod"),
+        ];
+        assert_eq!(search(items, "prime", SearchMode::Word), ["Soma Prime", "Prime Blueprint"]);
+    }
+
+    #[test]
+    fn path_filter_matches_whole_lotus_path_segments() {
+        let items = || {
+            vec![
+                item("Lith A1 Intact", "/Lotus/Types/Game/Projections/T1VoidProjectionA1Bronze", "Relic"),
+                item("Soma Prime", "/Lotus/Weapons/Tenno/Rifle/SomaPrime", "Rifle"),
+                item("Primary Merciless", "/Lotus/Upgrades/CosmeticEnhancers/Offensive/PrimaryArcanes/Merciless", "Arcane"),
+            ]
+        };
+        let by_path = |path: &str| -> Vec<String> {
+            filter_items_by_path(items().into_iter(), Some(path)).map(|item| item.name).collect()
+        };
+        assert_eq!(by_path("Projections"), ["Lith A1 Intact"]);
+        assert_eq!(by_path("Weapons/Tenno"), ["Soma Prime"]);
+        assert_eq!(by_path("/weapons/tenno/"), ["Soma Prime"]);
+        assert_eq!(by_path("Tenno/Weapons"), Vec::<String>::new());
+        assert_eq!(by_path("Primary"), Vec::<String>::new());
+        assert_eq!(by_path("PrimaryArcanes"), ["Primary Merciless"]);
+    }
+
+    #[test]
+    fn relic_short_names_keep_multi_word_codes() {
+        let short_name = |name: &str| item(name, "/Lotus/Types/Game/Projections/T5VoidProjectionRequiemBronze", "Relic").get_relic_short_name();
+        assert_eq!(short_name("Lith Requiem I Relic"), "Lith Requiem I");
+        assert_eq!(short_name("Lith Requiem II Relic"), "Lith Requiem II");
+        assert_eq!(short_name("Lith K3 Relic"), "Lith K3");
+        assert_eq!(short_name("Lith K3 Intact"), "Lith K3");
+        assert_eq!(short_name("Axi Relic"), "Axi Relic");
+    }
+
+    // A weapon with an image and a market page, and a relic with a reward, so every format has something to show
+    fn snapshot_items() -> Vec<Item> {
+        let mut soma = item("Soma Prime", "/Lotus/Weapons/Tenno/Rifle/SomaPrime", "Rifle");
+        soma.imageName = Some(String::from("soma-prime.png"));
+        soma.warframeMarket = Some(WarframeMarket { id: String::from("1"), urlName: String::from("soma_prime_set") });
+        let relic = relic("Lith A1 Intact", "/Lotus/Types/Game/Projections/T1VoidProjectionA1Bronze", &[("Rare", 2.0, "Soma Prime Barrel")]);
+        vec![soma, relic]
+    }
+
+    #[test]
+    fn renders_default_format() {
+        assert_eq!(
+            render_string(snapshot_items(), OutputFormat::Default),
+            r#"┌──────────────────────────────────────────────────────────────────────────────┐
+│ Name: Soma Prime                                                             │
+│ UniqueName: /Lotus/Weapons/Tenno/Rifle/SomaPrime                             │
+│ Type: Rifle                                                                  │
+│ Tradable: true                                                               │
+└──────────────────────────────────────────────────────────────────────────────┘
+┌──────────────────────────────────────────────────────────────────────────────┐
+│ Name: Lith A1 Intact                                                         │
+│ UniqueName: /Lotus/Types/Game/Projections/T1VoidProjectionA1Bronze           │
+│ Type: Relic                                                                  │
+│ Tradable: true                                                               │
+│   - [Rare 2.00%] Soma Prime Barrel                                           │
+└──────────────────────────────────────────────────────────────────────────────┘
+"#
+        );
+    }
+
+    #[test]
+    fn renders_names_format() {
+        assert_eq!(
+            render_string(snapshot_items(), OutputFormat::Search),
+            r#"Soma Prime
+Lith A1 Intact
+"#
+        );
+    }
+
+    #[test]
+    fn renders_ndjson_format() {
+        assert_eq!(
+            render_string(snapshot_items(), OutputFormat::Ndjson),
+            r#"{"name":"Soma Prime","uniqueName":"/Lotus/Weapons/Tenno/Rifle/SomaPrime","type":"Rifle","tradable":true,"warframeMarket":{"id":"1","urlName":"soma_prime_set"},"imageName":"soma-prime.png"}
+{"name":"Lith A1 Intact","uniqueName":"/Lotus/Types/Game/Projections/T1VoidProjectionA1Bronze","type":"Relic","tradable":true,"rewards":[{"rarity":"Rare","chance":2.0,"item":{"name":"Soma Prime Barrel","uniqueName":"/Lotus/Test/Soma Prime Barrel"}}]}
+"#
+        );
+    }
+
+    #[test]
+    fn renders_csv_format() {
+        assert_eq!(
+            render_string(snapshot_items(), OutputFormat::Csv),
+            r#"name,uniqueName,type,tradable,category
+Soma Prime,/Lotus/Weapons/Tenno/Rifle/SomaPrime,Rifle,true,
+Lith A1 Intact,/Lotus/Types/Game/Projections/T1VoidProjectionA1Bronze,Relic,true,
+"#
+        );
+    }
+
+    #[test]
+    fn renders_json_format() {
+        assert_eq!(
+            render_string(snapshot_items(), OutputFormat::Json),
+            r#"[{"name":"Soma Prime","uniqueName":"/Lotus/Weapons/Tenno/Rifle/SomaPrime","type":"Rifle","tradable":true,"warframeMarket":{"id":"1","urlName":"soma_prime_set"},"imageName":"soma-prime.png"},{"name":"Lith A1 Intact","uniqueName":"/Lotus/Types/Game/Projections/T1VoidProjectionA1Bronze","type":"Relic","tradable":true,"rewards":[{"rarity":"Rare","chance":2.0,"item":{"name":"Soma Prime Barrel","uniqueName":"/Lotus/Test/Soma Prime Barrel"}}]}]
+"#
+        );
+    }
+
+    #[test]
+    fn renders_line_format() {
+        assert_eq!(
+            render_string(snapshot_items(), OutputFormat::Line),
+            "Soma Prime\tRifle\ttrue\nLith A1 Intact\tRelic\ttrue\n"
+        );
+    }
+
+    #[test]
+    fn renders_markdown_format() {
+        assert_eq!(
+            render_string(snapshot_items(), OutputFormat::Markdown),
+            r#"| name | type | category | tradable |
+| --- | --- | --- | --- |
+| Soma Prime | Rifle |  | true |
+| Lith A1 Intact | Relic |  | true |
+"#
+        );
+    }
+
+    #[test]
+    fn renders_image_format() {
+        assert_eq!(
+            render_string(snapshot_items(), OutputFormat::Image),
+            r#"https://cdn.warframestat.us/img/soma-prime.png
+"#
+        );
+    }
+
+    #[test]
+    fn renders_market_format() {
+        assert_eq!(
+            render_string(snapshot_items(), OutputFormat::Market),
+            r#"https://warframe.market/items/soma_prime_set
+"#
+        );
+    }
+
+    #[test]
+    fn renders_yaml_format() {
+        assert_eq!(
+            render_string(snapshot_items(), OutputFormat::Yaml),
+            r#"- name: Soma Prime
+  uniqueName: /Lotus/Weapons/Tenno/Rifle/SomaPrime
+  type: Rifle
+  tradable: true
+  warframeMarket:
+    id: '1'
+    urlName: soma_prime_set
+  imageName: soma-prime.png
+- name: Lith A1 Intact
+  uniqueName: /Lotus/Types/Game/Projections/T1VoidProjectionA1Bronze
+  type: Relic
+  tradable: true
+  rewards:
+  - rarity: Rare
+    chance: 2.0
+    item:
+      name: Soma Prime Barrel
+      uniqueName: /Lotus/Test/Soma Prime Barrel
+"#
+        );
+    }
+
+    #[test]
+    fn renders_toml_format() {
+        assert_eq!(
+            render_string(snapshot_items(), OutputFormat::Toml),
+            r#"[[items]]
+name = "Soma Prime"
+uniqueName = "/Lotus/Weapons/Tenno/Rifle/SomaPrime"
+type = "Rifle"
+tradable = true
+imageName = "soma-prime.png"
+
+[items.warframeMarket]
+id = "1"
+urlName = "soma_prime_set"
+
+[[items]]
+name = "Lith A1 Intact"
+uniqueName = "/Lotus/Types/Game/Projections/T1VoidProjectionA1Bronze"
+type = "Relic"
+tradable = true
+
+[[items.rewards]]
+rarity = "Rare"
+chance = 2.0
+
+[items.rewards.item]
+name = "Soma Prime Barrel"
+uniqueName = "/Lotus/Test/Soma Prime Barrel"
+"#
+        );
+    }
+
+    #[test]
+    fn refinement_suffixes_are_read_from_relic_names() {
+        let unique_name = "/Lotus/Types/Game/Projections/T1VoidProjectionA1Bronze";
+        let radiant = item("Lith A1 Radiant", unique_name, "Relic");
+        assert_eq!(radiant.get_relic_short_name(), "Lith A1");
+        assert_eq!(radiant.refinement(), Some(Refinement::Radiant));
+        assert_eq!(item("Lith A1 Exceptional", unique_name, "Relic").refinement(), Some(Refinement::Exceptional));
+        assert_eq!(item("Lith A1 Relic", unique_name, "Relic").refinement(), None);
+    }
+
+    #[test]
+    fn refinement_filter_keeps_relics_without_a_known_refinement() {
+        let unique_name = "/Lotus/Types/Game/Projections/T1VoidProjectionA1Bronze";
+        let items = vec![
+            item("Lith A1 Intact", unique_name, "Relic"),
+            item("Lith A1 Radiant", unique_name, "Relic"),
+            item("Lith A1 Relic", unique_name, "Relic"),
+        ];
+        let kept: Vec<Item> = filter_items_by_refinement(items.into_iter(), &[Refinement::Radiant]).collect();
+        assert_eq!(names(&kept), ["Lith A1 Radiant", "Lith A1 Relic"]);
+    }
+
+    // Relics of every era mixed with plain items, enough to span several --parallel chunks
+    fn synthetic_items(count: usize) -> Vec<Item> {
+        let eras = ["Lith", "Meso", "Neo", "Axi"];
+        (0..count)
+            .map(|index| {
+                if index % 2 == 0 {
+                    let era = eras[index / 2 % eras.len()];
+                    let tier = index / 2 % eras.len() + 1;
+                    item(
+                        &format!("{} P{} Intact", era, index),
+                        &format!("/Lotus/Types/Game/Projections/T{}VoidProjectionP{}Bronze", tier, index),
+                        "Relic",
+                    )
+                } else {
+                    item(&format!("Weapon {} Prime", index), &format!("/Lotus/Weapons/Tenno/Weapon{}", index), "Rifle")
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parallel_filtering_matches_sequential_filtering() {
+        let args = Args::parse_from([env!("CARGO_PKG_NAME"), "--fuzzy", "prime"]);
+        // The best match sits in a later chunk, so it only comes first if the chunks are ranked together
+        let items = || {
+            let mut items = synthetic_items(3 * PARALLEL_CHUNK_SIZE);
+            items[2 * PARALLEL_CHUNK_SIZE + 1].name = String::from("Prime");
+            items
+        };
+
+        let sequential_counts = StageCounts::default();
+        let sequential: Vec<Item> = filter_items(items().into_iter(), &args, &sequential_counts).collect();
+        let parallel_counts = StageCounts::default();
+        let parallel = filter_items_in_parallel(items(), &args, &parallel_counts);
+
+        assert_eq!(parallel[0].name, "Prime");
+        assert_eq!(names(&parallel), names(&sequential));
+        assert_eq!(parallel_counts.counts.into_inner(), sequential_counts.counts.into_inner());
+    }
+
+    #[test]
+    fn search_terms_ignore_extra_whitespace() {
+        assert_eq!(search(soma_items(), "  Soma  Prime  ", SearchMode::Contains), ["Soma Prime"]);
+    }
+
+    #[test]
+    fn prefix_search_terms_ignore_extra_whitespace() {
+        assert_eq!(search(soma_items(), "  soma  prime", SearchMode::Prefix), ["Soma Prime"]);
+    }
+
+    #[test]
+    fn names_format_lists_duplicate_names_in_input_order() {
+        let items = vec![
+            item("Soma Prime", "/Lotus/Weapons/Tenno/Rifle/SomaPrime", "Rifle"),
+            item("Ash Prime", "/Lotus/Powersuits/Ninja/AshPrime", "Warframe"),
+            item("Soma Prime", "/Lotus/Weapons/Tenno/Rifle/SomaPrimeDuplicate", "Rifle"),
+        ];
+        assert_eq!(render_string(items, OutputFormat::Search), "Soma Prime\nAsh Prime\nSoma Prime\n");
+    }
+
+    #[test]
+    fn names_format_with_relic_lists_each_short_name_once_in_first_seen_order() {
+        let meso = "/Lotus/Types/Game/Projections/T2VoidProjectionB2Bronze";
+        let lith = "/Lotus/Types/Game/Projections/T1VoidProjectionA1Bronze";
+        let items = vec![
+            item("Meso B2 Intact", meso, "Relic"),
+            item("Lith A1 Intact", lith, "Relic"),
+            item("Meso B2 Radiant", meso, "Relic"),
+            item("Lith A1 Exceptional", lith, "Relic"),
+        ];
+        let mut args = default_args(OutputFormat::Search);
+        args.relic = Some(Vec::new());
+        let mut out = Vec::new();
+        log_items(items, ItemLogger::new(&args, &mut out).unwrap()).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "Meso B2\nLith A1\n");
+    }
+
+    #[test]
+    fn wrapped_lines_after_the_first_are_indented() {
+        let description = "An artifact containing Orokin secrets. It can only be opened through the power of the Void.";
+        let lines = wrap_text(description, "Description:", 30, 4);
+        assert!(lines.len() > 2);
+        assert!(lines[0].starts_with("Description: An"));
+        for line in &lines[1..] {
+            assert!(line.starts_with("    ") && !line.starts_with("     "), "{:?} isn't indented by 4", line);
+        }
+    }
+
+    #[test]
+    fn byte_order_marks_are_stripped() {
+        assert_eq!(strip_bom("\u{feff}[]"), "[]");
+        assert_eq!(strip_bom("[]"), "[]");
+        assert_eq!(strip_bom("[\u{feff}]"), "[\u{feff}]");
+    }
+
+    #[test]
+    fn json_starting_with_a_byte_order_mark_is_parsed() {
+        let path = env::temp_dir().join(format!("wf_api_quick_{}_bom.json", process::id()));
+        std::fs::write(&path, format!("\u{feff}{}", SMALL_EXPORT)).unwrap();
+        let items = read_items_or_exit(&InputSource::File(path.to_str().unwrap()), None, true, &mut BTreeMap::new());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(names(&items), ["Soma Prime", "Lith A1 Intact"]);
+    }
+
+    // Stands in for a full disk, so render_to's write errors can be checked
+    struct FailingWriter;
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn render_to_returns_write_errors() {
+        for format in [OutputFormat::Default, OutputFormat::Search, OutputFormat::Json, OutputFormat::Csv] {
+            let err = render_to(&mut FailingWriter, snapshot_items(), format).unwrap_err();
+            assert_eq!(err.to_string(), "disk full", "{:?}", format);
+        }
+    }
+
+    // Streaming would only rank each chunk against itself, so --fuzzy and --ndjson can't be combined
+    #[test]
+    fn fuzzy_conflicts_with_ndjson() {
+        let err = Args::try_parse_from([env!("CARGO_PKG_NAME"), "--ndjson", "--fuzzy", "prime"]).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+    }
+
+    // Prices come from the run's one MarketPrices, so nothing is fetched for an urlName it already looked up
+    #[test]
+    fn logger_shows_prices_the_run_already_looked_up() {
+        let mut args = default_args(OutputFormat::Default);
+        args.width = Some(80);
+        args.color = ColorChoice::Never;
+        args.prices = true;
+        let mut prices = MarketPrices::new(&args);
+        prices.cache.insert(String::from("soma_prime_set"), Some(42));
+        let mut out = Vec::new();
+        log_items(snapshot_items(), ItemLogger::new(&args, &mut out).unwrap().with_prices(&mut prices)).unwrap();
+        assert!(String::from_utf8(out).unwrap().contains("│ Price: 42 platinum "));
+    }
+
+    #[test]
+    fn saving_prices_writes_only_what_changed_to_the_cache_file() {
+        let path = env::temp_dir().join(format!("wf_api_quick_{}_prices.json", process::id()));
+        let mut args = default_args(OutputFormat::Default);
+        args.price_cache = Some(path.to_str().unwrap().to_string());
+        let mut prices = MarketPrices::new(&args);
+        prices.save();
+        assert!(!path.exists());
+
+        prices.stored_prices.insert(String::from("soma_prime_set"), CachedPrice { price: Some(42), fetched_at: Utc::now() });
+        prices.stored_prices_changed = true;
+        prices.save();
+        let stored = read_price_cache(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(stored["soma_prime_set"].price, Some(42));
+    }
+}